@@ -25,6 +25,45 @@
 //!
 //! This will help you separate and organise multiple languages.
 //!
+//! ### Where the database is kept
+//!
+//! vocab looks for `vocab.sqlite` in the current directory, then in each parent directory in turn,
+//! so you can practice from anywhere inside the directory you ran `init` in. If it doesn't find one
+//! it uses the global database in `~/.local/share/vocab/`, which you can create with
+//!
+//! ```shell
+//! vocab init --global
+//! ```
+//!
+//! You can also choose a database explicitly, either with `--db` or by setting `VOCAB_DB`
+//!
+//! ```shell
+//! vocab --db ~/languages/japanese.sqlite init
+//! export VOCAB_DB=~/languages/japanese.sqlite
+//! vocab review
+//! ```
+//!
+//! ### Decks
+//!
+//! Instead of using a directory for each language, you can keep several languages in one database as
+//! decks. A new database starts with a deck called `default`. Create a deck for each language,
+//! optionally naming the languages, which are then shown when you are asked for a translation.
+//!
+//! ```shell
+//! vocab deck create spanish --local English --foreign Spanish
+//! vocab deck use spanish
+//! ```
+//!
+//! The deck you `use` is remembered, and every other command (`add`, `single`, `review`, `import`,
+//! `export` and so on) only works with the words in that deck until you switch to another.
+//!
+//! ```shell
+//! vocab deck list
+//! vocab deck delete japanese
+//! ```
+//!
+//! Deleting a deck deletes every word in it, so you will be asked to confirm unless you pass `--yes`.
+//!
 //! ### Adding new words
 //!
 //! `add <local> <foreign>` will let you add a new word as you learn it. `<local>` should be the
@@ -36,6 +75,158 @@
 //! vocab add japan 日本
 //! ```
 //!
+//! A word can have more than one translation, add each of them separately. When you are asked for
+//! the translation any of them will be accepted.
+//!
+//! ```shell
+//! vocab add hello こんにちは
+//! vocab add hello もしもし
+//! ```
+//!
+//! ### Fixing mistakes
+//!
+//! If you made a typo when adding a word you can correct either side of it with `edit`, everything
+//! vocab has recorded about how well you know the word is kept. As with tags, the word can be either
+//! the local or foreign one.
+//!
+//! ```shell
+//! vocab edit yse --local yes
+//! vocab edit yes --foreign はい
+//! ```
+//!
+//! Or remove it altogether
+//!
+//! ```shell
+//! vocab remove yes
+//! ```
+//!
+//! Both ask you to confirm each change unless you pass `--yes`.
+//!
+//! If a word has more than one translation, rename them one at a time using their other word.
+//!
+//! ### Alternative answers
+//!
+//! Some words can be spelt or said in more than one way. Use `--alt` to accept other answers for the
+//! local word, and `--foreign-alt` for the foreign word. Both can be given more than once.
+//!
+//! ```shell
+//! vocab add color couleur --alt colour
+//! ```
+//!
+//! You can change the alternatives for a word you have already added. The word can be either the
+//! local or foreign one.
+//!
+//! ```shell
+//! vocab alt add television tv
+//! vocab alt remove television tv
+//! ```
+//!
+//! ### Optional words and separators
+//!
+//! Words can be written the way most word lists write them. Anything in brackets is optional, and `;`
+//! separates answers that are all right, so either can be typed. `/` separates answers too, but only
+//! with a space either side of it, so words like `km/h` and `and/or` stay whole. They work in
+//! alternatives too.
+//!
+//! ```shell
+//! vocab add "(to) eat" manger
+//! vocab add "car; automobile" voiture
+//! vocab add "bike / bicycle" vélo
+//! ```
+//!
+//! Here `to eat` and `eat` are both accepted for manger, and `car` or `automobile` for voiture. When you
+//! get a word wrong, each answer is listed separately. Each answer can have up to 8 optional parts.
+//!
+//! ### Tags
+//!
+//! Tags let you group words by topic. Add them when you add a word, or later with `tag add`. As with
+//! alternatives the word can be either the local or foreign one.
+//!
+//! ```shell
+//! vocab add bread パン --tag food --tag "jlpt n5"
+//! vocab tag add train travel
+//! vocab tag remove bread "jlpt n5"
+//! ```
+//!
+//! `tag list` shows every tag and how many words have it, or the tags on a single word.
+//!
+//! ```shell
+//! vocab tag list
+//! vocab tag list bread
+//! ```
+//!
+//! `single`, `endless` and `export` can be limited to words with a tag using `--tag`
+//!
+//! ```shell
+//! vocab endless --tag food
+//! ```
+//!
+//! ### Part of speech, gender and plural
+//!
+//! Words can say what part of speech they are, and foreign nouns can have a gender and plural.
+//!
+//! ```shell
+//! vocab add house Haus --part-of-speech noun --gender neuter --plural Häuser
+//! vocab edit go --part-of-speech verb
+//! ```
+//!
+//! The part of speech is shown in every prompt, the gender and plural are shown with the foreign word.
+//! Genders can be given as `masculine`, `feminine`, `neuter` or `common`, or just their first letter.
+//!
+//! If the deck's locale is German (`de`), French (`fr`), Spanish (`es`), Italian (`it`), Portuguese
+//! (`pt`), Dutch (`nl`) or Swedish (`sv`) the foreign word is shown with its article, eg `das haus`,
+//! and you can practice genders by article. Decks without a locale use the name of their foreign
+//! language instead, if it's one of those in English. Words can only be given the genders their deck's
+//! language has, so common nouns are refused in German.
+//!
+//! ```shell
+//! vocab deck set --locale de
+//! ```
+//!
+//! ```shell
+//! vocab genders
+//! ```
+//!
+//! ```text
+//! der, die or das? haus
+//! Your guess: das
+//! Correct!
+//! ```
+//!
+//! Practising genders doesn't change a word's statistics or when it's next due.
+//!
+//! ### Notes, examples and mnemonics
+//!
+//! Words can have notes, example sentences and a mnemonic to help you remember them. If you get a
+//! word wrong you'll be shown its note, mnemonic and examples.
+//!
+//! ```shell
+//! vocab add yes はい --mnemonic "Hi! Yes?" --example "はい、そうです。"
+//! vocab edit yes --note "Also used to show you are listening" --example "はい、分かりました。"
+//! ```
+//!
+//! `edit` adds examples to the ones a word already has, and replaces its note and mnemonic. Use an
+//! empty note or mnemonic, eg `--note ""`, to remove it.
+//!
+//! ### Readings
+//!
+//! Words written in another script can have a reading, eg the kana for a word written in kanji or a
+//! romanisation. The reading is accepted as an answer in place of the foreign word.
+//!
+//! ```shell
+//! vocab add japan 日本 --reading にほん
+//! vocab edit japan --reading ""
+//! ```
+//!
+//! By default the reading is shown once you've answered, so it doesn't give the answer away. To see it
+//! alongside the foreign word when you're asked for the local one, change the setting for the current
+//! deck:
+//!
+//! ```shell
+//! vocab deck set --show-reading prompt
+//! vocab deck set --show-reading answer
+//! ```
+//!
 //! ### Try a single word
 //!
 //! You can try guessing a single word at a time
@@ -46,12 +237,177 @@
 //!
 //! ### Endless Mode
 //!
-//! When you run the program with no other arguments it will enter endless mode (use ctrl+c to quit)
+//! When you run the program with no other arguments it will enter endless mode. Type `:q` at any
+//! prompt, or use ctrl+c, to stop. You'll be shown how many words you saw, how many you got right, the
+//! ones you missed and how long you spent. A word you hadn't answered yet isn't counted. `:q` keeps the
+//! answer you just gave when asked if you were right, but ctrl+c doesn't. The same summary is shown
+//! after `review`, `flash` and `genders`.
 //!
 //! ```shell
 //! vocab
 //! ```
 //!
+//! ### Multiple choice
+//!
+//! New words are easier to recognise than to remember, so endless mode can give you options to choose
+//! from instead of typing the answer. Type the number of the right one. The wrong options come from
+//! other words in the deck, preferring ones that share a tag, are in the same script and are about as
+//! long as the answer.
+//!
+//! ```shell
+//! vocab endless --choices 4
+//! ```
+//!
+//! ### Accents and keyboards
+//!
+//! Answers are compared after Unicode normalisation, so the same word typed on different keyboards still
+//! matches, eg an accent typed as a separate character, or full width letters and half width kana from a
+//! Japanese input method.
+//!
+//! Each deck can also accept answers without their accents, umlauts and other diacritics. You'll still
+//! be shown the correct spelling.
+//!
+//! ```shell
+//! vocab deck set --ignore-diacritics true
+//! ```
+//!
+//! ### Capital letters
+//!
+//! Words keep the case you add them in, so German nouns keep their capitals, but you can answer and
+//! look words up in any case. Each deck can make the case of foreign answers matter instead, and can
+//! give the locale of its foreign language for languages with their own case rules, such as the dotted
+//! and dotless i of Turkish.
+//!
+//! ```shell
+//! vocab deck set --case-sensitive true
+//! vocab deck set --locale tr
+//! ```
+//!
+//! Run `vocab deck set` on its own to see the current deck's settings.
+//!
+//! ### Typing in Latin letters
+//!
+//! If you don't have a keyboard for a deck's foreign language, the deck can accept foreign answers
+//! typed in Latin letters and turn them into its script before comparing them. The schemes are
+//! `romaji` (Hepburn romaji for hiragana and katakana, eg `nihon` for にほん), `cyrillic`, `greek` and
+//! `hangul` (Revised Romanization). Use `none` to turn it off again.
+//!
+//! ```shell
+//! vocab deck set --transliteration romaji
+//! ```
+//!
+//! For Japanese, romaji is compared against the word's reading as well as the word itself, so add
+//! readings to words written in kanji.
+//!
+//! ### Near misses
+//!
+//! If your answer is only a typo or two away from being right you'll be told what you typed and what
+//! was expected. Words of three letters or fewer have to be exact, longer words can have one typo, and
+//! words of eight letters or more can have two.
+//!
+//! By default a near miss is still wrong, but each deck can choose to count near misses as correct, or
+//! as a typo. A typo isn't counted as correct but it doesn't set the word back to the start of its
+//! review schedule either, and it's recorded separately in the review log.
+//!
+//! ```shell
+//! vocab deck set --near-miss typo
+//! vocab deck set --near-miss correct
+//! vocab deck set --near-miss wrong
+//! ```
+//!
+//! ### Spotting mistakes
+//!
+//! When you get a word wrong, your answer is lined up with the closest right answer, letter by letter,
+//! with the differences in colour. Letters you missed are shown as `_`. If the output isn't a terminal,
+//! or `NO_COLOR` is set, the differences are marked with `^` instead.
+//!
+//! ```
+//! Incorrect! The actual translation is internationale
+//!   You typed: intrenat_onal_
+//!   Expected:  internationale
+//!                 ^^   ^    ^
+//! ```
+//!
+//! ### I was right
+//!
+//! If an answer is marked wrong but you're sure it's right, type `!` when you're asked. The answer is
+//! counted as correct instead, and you'll be asked whether to accept it for that word from now on.
+//!
+//! So that practice isn't held up after every wrong answer you're only asked after near misses, unless
+//! you change the setting for the current deck, eg if you often know other words for the same thing:
+//!
+//! ```shell
+//! vocab deck set --overrule-wrong true
+//! ```
+//!
+//! ```
+//! Translate: colour
+//! Your guess: teinte
+//! Incorrect! The actual translation is couleur
+//! Press Enter to carry on, or type ! if you were right: !
+//! Marked as correct
+//! Accept teinte as an answer for colour? [y/N] y
+//! teinte will be accepted from now on
+//! ```
+//!
+//! ### Hints
+//!
+//! If you're stuck, type `?` instead of an answer. The first hint shows how long the answer is, the next
+//! shows its first letter, and each one after that shows another letter.
+//!
+//! ```
+//! Translate: (to) eat
+//! Your guess: ?
+//! Hint: _ _ _ _ _ _
+//! Your guess: ?
+//! Hint: m _ _ _ _ _
+//! Your guess: manger
+//! Correct, with 2 hints
+//! ```
+//!
+//! An answer you needed hints for is counted separately, as neither right nor wrong, and the summary at
+//! the end shows how many there were. The word is still scheduled as one you remembered, just not as
+//! far out as a clean recall.
+//!
+//! ### Choosing which words come up
+//!
+//! Both `single` and endless mode pick words at random by default. Use `--strategy` to change how
+//! they are chosen:
+//!
+//! - `random` every word is equally likely
+//! - `weakest` the word you get right least often
+//! - `least-recent` the word you haven't seen for the longest
+//! - `weighted` random, but words you get wrong come up more often
+//! - `new` words you have never been asked before
+//!
+//! ```shell
+//! vocab endless --strategy weakest
+//! ```
+//!
+//! ### Review mode
+//!
+//! `review` uses spaced repetition to decide which words you see. Each word is scheduled for a
+//! future date based on how well you knew it, words you keep getting wrong come back quickly and
+//! words you know well are left for longer. Review stops once nothing is due and tells you when the
+//! next word will be. Answering a word before it's due, eg in endless mode, still counts towards your
+//! statistics but doesn't change when it's next due.
+//!
+//! ```shell
+//! vocab review
+//! ```
+//!
+//! ### Flashcards
+//!
+//! Typing out long phrases gets tedious, so `flash` shows you each word like a flashcard instead. Press
+//! Enter when you're ready to see the answer, then say how well you remembered it: `again` if you
+//! didn't, `hard`, `good` or `easy`. The first letter or a number from 1 to 4 will do, and just
+//! pressing Enter is good. The grade decides when the word comes up again, just as typed answers do.
+//!
+//! ```shell
+//! vocab flash
+//! vocab flash --tag food
+//! ```
+//!
 //! ### Export the database
 //!
 //! You can export the database to csv file, either by naming it or via stdout
@@ -77,23 +433,70 @@
 //! ```
 //!
 //! If the database already contains the vocabulary in the csv file it will attempt to reconcile
-//! the differences, choosing whichever set has more guesses against it.
+//! the differences, choosing whichever set has more guesses against it. Every word has a unique id
+//! that is exported with it, so a word you have corrected with `edit` is still recognised, and the
+//! correction is copied across. Tags are exported too, and importing adds them to any tags the word
+//! already has.
+//!
+//! ### Upgrading the database
+//!
+//! When a new version of vocab changes how words are stored, your `vocab.sqlite` is upgraded
+//! automatically the next time you use it. A copy of the old database is kept next to it first,
+//! eg `vocab.sqlite.v1.bak`.
+//!
+//! Words added before optional parts and separators existed are read with them from then on, so a
+//! word containing `;`, or `/` with spaces around it, is treated as several answers, and brackets are
+//! treated as optional parts. Use `edit` to change any that weren't meant that way.
+//!
+//! You can see which version your database is at, and whether any upgrades are waiting, with
+//!
+//! ```shell
+//! vocab migrate --status
+//! ```
+//!
+//! and apply them without doing anything else with
+//!
+//! ```shell
+//! vocab migrate
+//! ```
+//!
+//! If your database was created by a newer version of vocab than the one you are running, vocab
+//! will refuse to open it rather than risk damaging it.
 
+use std::env;
 use std::error::Error;
 use std::io;
-use std::io::{Read, Write};
+use std::io::{IsTerminal, Read, Write};
+use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
 use std::{fmt, fs};
 
+use anstyle::{AnsiColor, Style};
+use chrono::{DateTime, Local, Utc};
+use diesel::result::Error as DieselError;
+use rand::seq::SliceRandom;
 use structopt::StructOpt;
+use unicode_width::UnicodeWidthStr;
 
-use std::path::Path;
-use vocab::{CsvReader, CsvWriter, ExporterError, Guess, Translation, VocabStore, VocabStoreError};
+use std::path::{Path, PathBuf};
+use vocab::{
+    choices, closest_answer, diff, find_database, global_database, parse_answers, Alternates,
+    CsvReader, CsvWriter, Deck, Difference, Direction, DueForReview, ExporterError, Gender, Grade,
+    Guess, LeastRecentlyReviewed, Match, MigrationStatus, NearMiss, NewWordsFirst, Outcome,
+    SelectionStrategy, ShowReading, Translation, UniformRandom, VocabStore, VocabStoreError,
+    WeakestFirst, WeightedRandom, DATABASE_FILE, MAX_OPTIONAL_PARTS,
+};
 
 /// For helping remember vocabulary in a new language.
 ///
 /// Add new words with `vocab add`, then test yourself by running vocab in endless mode.
 #[derive(StructOpt)]
 struct VocabApp {
+    /// The database to use. Without this vocab uses the nearest vocab.sqlite in the current
+    /// directory or its parents, or ~/.local/share/vocab/vocab.sqlite if there isn't one.
+    #[structopt(long, env = "VOCAB_DB", global = true, parse(from_os_str))]
+    db: Option<PathBuf>,
     #[structopt(subcommand)]
     subcommand: Option<Command>,
 }
@@ -101,26 +504,327 @@ struct VocabApp {
 #[derive(StructOpt)]
 enum Command {
     /// Initialise the database
-    Init,
+    Init {
+        /// Create the database in ~/.local/share/vocab so it can be used from anywhere
+        #[structopt(long)]
+        global: bool,
+    },
     /// Add a new word to the database
-    Add { local: String, foreign: String },
+    Add {
+        local: String,
+        foreign: String,
+        /// Another answer accepted for the local word, can be given more than once
+        #[structopt(long = "alt")]
+        alt: Vec<String>,
+        /// Another answer accepted for the foreign word, can be given more than once
+        #[structopt(long = "foreign-alt")]
+        foreign_alt: Vec<String>,
+        /// Tag the word, can be given more than once
+        #[structopt(long = "tag")]
+        tag: Vec<String>,
+        #[structopt(flatten)]
+        details: DetailsOpt,
+    },
+    /// Remove a word, local or foreign, and everything recorded about it
+    Remove {
+        word: String,
+        /// Don't ask for confirmation
+        #[structopt(short, long)]
+        yes: bool,
+    },
+    /// Correct a word, local or foreign, keeping everything recorded about it
+    Edit {
+        word: String,
+        /// The new local word
+        #[structopt(long, required_unless_one = &[
+            "foreign",
+            "reading",
+            "part-of-speech",
+            "gender",
+            "plural",
+            "note",
+            "example",
+            "mnemonic",
+        ])]
+        local: Option<String>,
+        /// The new foreign word
+        #[structopt(long)]
+        foreign: Option<String>,
+        #[structopt(flatten)]
+        details: DetailsOpt,
+        /// Don't ask for confirmation
+        #[structopt(short, long)]
+        yes: bool,
+    },
+    /// Manage the alternative answers accepted for a word
+    Alt(AltCommand),
+    /// Manage the tags on a word
+    Tag(TagCommand),
+    /// Manage decks, each deck is a separate set of words
+    Deck(DeckCommand),
     /// Get a single word from the database
-    Single,
+    Single {
+        #[structopt(flatten)]
+        strategy: StrategyOpt,
+        #[structopt(flatten)]
+        tag: TagOpt,
+    },
     /// (default) Practice as many words as you like
-    Endless,
+    Endless {
+        #[structopt(flatten)]
+        strategy: StrategyOpt,
+        #[structopt(flatten)]
+        tag: TagOpt,
+        /// Choose the answer from this many options instead of typing it, at least 2
+        #[structopt(long, validator = at_least_two)]
+        choices: Option<usize>,
+    },
+    /// Review the words that are due, until there are none left
+    Review,
+    /// Flashcards, see the answer when you're ready and say how well you remembered it
+    Flash {
+        #[structopt(flatten)]
+        strategy: StrategyOpt,
+        #[structopt(flatten)]
+        tag: TagOpt,
+    },
+    /// Practice the genders of the words that have one, eg der, die or das?
+    Genders {
+        #[structopt(flatten)]
+        tag: TagOpt,
+    },
     /// Export the database to a csv
     Export {
         #[structopt(short, long)]
         file: Option<String>,
+        #[structopt(flatten)]
+        tag: TagOpt,
     },
     /// Import the database from a csv
     Import {
         #[structopt(short, long)]
         file: Option<String>,
     },
+    /// Upgrade the database to the latest schema
+    Migrate {
+        /// Show the schema version and any pending migrations without applying them
+        #[structopt(long)]
+        status: bool,
+    },
+}
+
+#[derive(StructOpt)]
+enum AltCommand {
+    /// Accept another answer for a word, local or foreign
+    Add { word: String, alternate: String },
+    /// Stop accepting an alternative answer for a word, local or foreign
+    Remove { word: String, alternate: String },
+}
+
+#[derive(StructOpt)]
+enum TagCommand {
+    /// Tag a word, local or foreign
+    Add { word: String, tag: String },
+    /// Remove a tag from a word, local or foreign
+    Remove { word: String, tag: String },
+    /// List every tag, or the tags on a word
+    List { word: Option<String> },
+}
+
+#[derive(StructOpt)]
+enum DeckCommand {
+    /// Create a new deck
+    Create {
+        name: String,
+        /// What your own language is called, eg English
+        #[structopt(long, default_value = "")]
+        local: String,
+        /// What the language you are learning is called, eg Japanese
+        #[structopt(long, default_value = "")]
+        foreign: String,
+    },
+    /// List every deck
+    List,
+    /// Switch to a deck, every other command will use it until you switch again
+    Use { name: String },
+    /// Delete a deck and every word in it
+    Delete {
+        name: String,
+        /// Don't ask for confirmation
+        #[structopt(short, long)]
+        yes: bool,
+    },
+    /// Change the settings of the current deck, or show them if none are given
+    Set {
+        /// When to show the reading of a foreign word, either alongside it in the prompt or only
+        /// once you've answered
+        #[structopt(long, possible_values = &["prompt", "answer"])]
+        show_reading: Option<ShowReading>,
+        /// How to grade answers that are only a typo away from being right: as correct, as
+        /// wrong, or as a typo that isn't counted as correct but doesn't set the word back
+        #[structopt(long, possible_values = &["correct", "wrong", "typo"])]
+        near_miss: Option<NearMiss>,
+        /// Whether to accept answers without their accents and other diacritics, the correct
+        /// spelling is still shown
+        #[structopt(long, possible_values = &["true", "false"])]
+        ignore_diacritics: Option<bool>,
+        /// Whether foreign answers in the wrong case are wrong, for languages where capitals
+        /// matter such as German
+        #[structopt(long, possible_values = &["true", "false"])]
+        case_sensitive: Option<bool>,
+        /// The locale of the foreign language, for its case rules and articles, eg tr for Turkish
+        /// or de for German. Use an empty locale to go back to the default rules
+        #[structopt(long)]
+        locale: Option<String>,
+        /// Lets foreign answers be typed in Latin letters, eg nihon for にほん
+        #[structopt(
+            long,
+            possible_values = &["romaji", "cyrillic", "greek", "hangul", "none"]
+        )]
+        transliteration: Option<String>,
+        /// Whether to be asked if you were right after every wrong answer, rather than only after
+        /// near misses
+        #[structopt(long, possible_values = &["true", "false"])]
+        overrule_wrong: Option<bool>,
+    },
 }
 
-const SQLITE_FILE: &str = "vocab.sqlite";
+#[derive(StructOpt)]
+struct DetailsOpt {
+    /// How the foreign word is read, eg its kana or a romanisation, also accepted as an answer
+    #[structopt(long)]
+    reading: Option<String>,
+    /// What kind of word it is, eg noun, verb, adjective (or adj)
+    #[structopt(long, name = "part-of-speech")]
+    part_of_speech: Option<String>,
+    /// The gender of the foreign word: masculine, feminine, neuter or common (or m, f, n, c)
+    #[structopt(long)]
+    gender: Option<String>,
+    /// The plural of the foreign word
+    #[structopt(long)]
+    plural: Option<String>,
+    /// Notes about the word
+    #[structopt(long)]
+    note: Option<String>,
+    /// A sentence using the word, can be given more than once
+    #[structopt(long = "example")]
+    example: Vec<String>,
+    /// Something to help you remember the word
+    #[structopt(long)]
+    mnemonic: Option<String>,
+}
+
+impl DetailsOpt {
+    fn is_empty(&self) -> bool {
+        self.reading.is_none()
+            && self.part_of_speech.is_none()
+            && self.gender.is_none()
+            && self.plural.is_none()
+            && self.note.is_none()
+            && self.example.is_empty()
+            && self.mnemonic.is_none()
+    }
+
+    /// Sets each of the details that were given, an empty string removes them, and adds the
+    /// examples
+    fn apply(&self, translation: &mut Translation, deck: &Deck) -> Result<(), AppError> {
+        let non_empty = |text: &String| Some(text.trim().to_string()).filter(|t| !t.is_empty());
+        if let Some(reading) = &self.reading {
+            translation.reading = non_empty(reading);
+        }
+        if let Some(part_of_speech) = &self.part_of_speech {
+            translation.part_of_speech = non_empty(part_of_speech)
+                .map(|p| p.parse())
+                .transpose()
+                .map_err(AppError::InvalidDetail)?;
+        }
+        if let Some(gender) = &self.gender {
+            translation.gender = non_empty(gender)
+                .map(|g| g.parse())
+                .transpose()
+                .map_err(AppError::InvalidDetail)?;
+            if let Some(gender) = translation
+                .gender
+                .filter(|g| !g.in_language(deck.language()))
+            {
+                let genders: Vec<String> = Gender::articles(deck.language())
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|(g, _)| g.to_string())
+                    .collect();
+                let (last, rest) = genders.split_last().expect("the language has genders");
+                return Err(AppError::InvalidDetail(format!(
+                    "Nouns can't be {} in this deck's language, use {} or {}",
+                    gender,
+                    rest.join(", "),
+                    last
+                )));
+            }
+        }
+        if let Some(plural) = &self.plural {
+            translation.plural = non_empty(plural);
+        }
+        if let Some(note) = &self.note {
+            translation.notes = non_empty(note);
+        }
+        if let Some(mnemonic) = &self.mnemonic {
+            translation.mnemonic = non_empty(mnemonic);
+        }
+        for example in &self.example {
+            translation.examples.add(example);
+        }
+        Ok(())
+    }
+}
+
+#[derive(StructOpt)]
+struct TagOpt {
+    /// Only use words with this tag
+    #[structopt(long)]
+    tag: Option<String>,
+}
+
+#[derive(StructOpt)]
+struct StrategyOpt {
+    /// How words are chosen: random, weakest, least-recent, weighted or new
+    #[structopt(long, default_value = "random")]
+    strategy: Strategy,
+}
+
+enum Strategy {
+    Random,
+    Weakest,
+    LeastRecent,
+    Weighted,
+    New,
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Strategy::Random),
+            "weakest" => Ok(Strategy::Weakest),
+            "least-recent" => Ok(Strategy::LeastRecent),
+            "weighted" => Ok(Strategy::Weighted),
+            "new" => Ok(Strategy::New),
+            _ => Err(format!("Unknown strategy: {}", s)),
+        }
+    }
+}
+
+impl StrategyOpt {
+    fn selection_strategy(&self) -> Box<dyn SelectionStrategy> {
+        match self.strategy {
+            Strategy::Random => Box::new(UniformRandom),
+            Strategy::Weakest => Box::new(WeakestFirst),
+            Strategy::LeastRecent => Box::new(LeastRecentlyReviewed),
+            Strategy::Weighted => Box::new(WeightedRandom),
+            Strategy::New => Box::new(NewWordsFirst),
+        }
+    }
+}
 
 #[derive(Debug)]
 enum AppError {
@@ -131,13 +835,26 @@ enum AppError {
     ExportFileAlreadyExists,
     ImportFileDoesNotExist,
     ExporterError(ExporterError),
+    WordNotFound(String),
+    TranslationExists(String, String),
+    NoDataDirectory,
+    InvalidDatabasePath,
+    InvalidDetail(String),
+    /// A word to rename that matches more than one translation, with the words of each
+    AmbiguousWord(String, Vec<(String, String)>),
+    /// The user asked to stop, with `:q`, the end of input or Ctrl+C
+    Quit,
 }
 
 impl Error for AppError {}
 
 impl fmt::Display for AppError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        write!(f, "{:?}", self)
+        match self {
+            AppError::IoError(e) => write!(f, "{}", e),
+            AppError::ExporterError(e) => write!(f, "{:?}", e),
+            _ => write!(f, "{:?}", self),
+        }
     }
 }
 
@@ -147,6 +864,12 @@ impl From<VocabStoreError> for AppError {
     }
 }
 
+impl From<DieselError> for AppError {
+    fn from(e: DieselError) -> Self {
+        AppError::VocabStoreError(e.into())
+    }
+}
+
 impl From<io::Error> for AppError {
     fn from(e: io::Error) -> Self {
         AppError::IoError(e)
@@ -160,6 +883,9 @@ impl From<ExporterError> for AppError {
 }
 
 fn main() {
+    if let Err(e) = handle_ctrl_c() {
+        eprintln!("Couldn't handle ctrl+c, use :q to stop instead: {}", e);
+    }
     // &* allows us to auto deref the Box<dyn Error>
     match app() {
         Ok(_) => std::process::exit(0),
@@ -169,42 +895,379 @@ fn main() {
         Err(AppError::VocabStoreError(VocabStoreError::NotInitialised)) => {
             eprintln!("Not initialised, run: ");
             eprintln!("    vocab init");
+            eprintln!("or, to use the same database from anywhere:");
+            eprintln!("    vocab init --global");
         }
         Err(AppError::VocabStoreError(VocabStoreError::DuplicateEntry)) => {
             eprintln!("Already stored that translation");
         }
+        Err(AppError::VocabStoreError(VocabStoreError::SchemaTooNew { found, supported })) => {
+            eprintln!(
+                "The database uses schema version {} but this version of vocab only supports up to version {}",
+                found, supported
+            );
+            eprintln!("Upgrade vocab to use this database");
+        }
+        Err(AppError::VocabStoreError(VocabStoreError::InvalidTag)) => {
+            eprintln!("Tags can't be empty");
+        }
+        Err(AppError::VocabStoreError(VocabStoreError::DeckNotFound)) => {
+            eprintln!("No deck with that name, see `vocab deck list`");
+        }
+        Err(AppError::VocabStoreError(VocabStoreError::DeckAlreadyExists)) => {
+            eprintln!("There is already a deck with that name");
+        }
+        Err(AppError::VocabStoreError(VocabStoreError::DeckInUse)) => {
+            eprintln!("Can't delete the deck you are using, switch to another with `vocab deck use <name>` first");
+        }
+        Err(AppError::VocabStoreError(VocabStoreError::TranslationNotFound)) => {
+            eprintln!("That translation is no longer in the deck");
+        }
+        Err(AppError::VocabStoreError(VocabStoreError::InvalidAnswer(answer))) => {
+            eprintln!(
+                "The brackets in {} don't match or there are too many of them, put them around up to {} optional parts of an answer, eg (to) eat",
+                answer, MAX_OPTIONAL_PARTS
+            );
+        }
         Err(AppError::NoTranslationsFound) => {
             eprintln!("No translations found, add with `vocab add <local> <foreign>");
         }
         Err(AppError::IncorrectGuessInSingleMode) => {
             // Nothing to do here, error message already given
         }
+        Err(AppError::ExporterError(ExporterError::CsvError(e))) => eprintln!("{}", e),
         Err(AppError::ExportFileAlreadyExists) => eprintln!("File already exists"),
         Err(AppError::ImportFileDoesNotExist) => eprintln!("File does not exists"),
-        Err(e) => eprintln!("Something went wrong {:?}", e),
+        Err(AppError::WordNotFound(word)) => eprintln!("No translations found for {}", word),
+        Err(AppError::TranslationExists(local, foreign)) => {
+            eprintln!("There is already a translation {} - {}", local, foreign);
+        }
+        Err(AppError::NoDataDirectory) => {
+            eprintln!(
+                "Couldn't find your data directory, use --db to choose where the database goes"
+            );
+        }
+        Err(AppError::InvalidDatabasePath) => eprintln!("The database path must be valid unicode"),
+        Err(AppError::InvalidDetail(message)) => eprintln!("{}", message),
+        Err(AppError::AmbiguousWord(word, translations)) => {
+            eprintln!(
+                "{} matches more than one translation, rename one at a time using its other word:",
+                word
+            );
+            for (local, foreign) in translations {
+                eprintln!("    {} - {}", local, foreign);
+            }
+        }
+        Err(AppError::Quit) => std::process::exit(0),
+        Err(e) => eprintln!("Something went wrong {}", e),
     }
     std::process::exit(1);
 }
 
 fn app() -> Result<(), AppError> {
-    match VocabApp::from_args().subcommand.unwrap_or(Command::Endless) {
-        Command::Init => {
-            VocabStore::init(SQLITE_FILE)?;
-            println!("Database initialised");
+    let default_command = Command::Endless {
+        strategy: StrategyOpt {
+            strategy: Strategy::Random,
+        },
+        tag: TagOpt { tag: None },
+        choices: None,
+    };
+    let app = VocabApp::from_args();
+    let command = app.subcommand.unwrap_or(default_command);
+    let db = database_path(app.db, &command)?;
+    match command {
+        Command::Init { .. } => {
+            if let Some(dir) = Path::new(&db).parent() {
+                if !dir.as_os_str().is_empty() {
+                    fs::create_dir_all(dir)?;
+                }
+            }
+            VocabStore::init(&db)?;
+            println!("Database initialised at {}", db);
+        }
+
+        Command::Add {
+            local,
+            foreign,
+            alt,
+            foreign_alt,
+            tag,
+            details,
+        } => {
+            let mut translation = Translation::new(&local, &foreign);
+            translation.local_alternates = Alternates::new(&alt);
+            translation.foreign_alternates = Alternates::new(&foreign_alt);
+            let store = VocabStore::from(&db)?;
+            details.apply(&mut translation, &store.current_deck()?)?;
+            store.add(&translation)?;
+            if !tag.is_empty() {
+                if let Some(translation) = store.find(&local, &foreign)? {
+                    for tag in &tag {
+                        store.add_tag(&translation, tag)?;
+                    }
+                }
+            }
+        }
+
+        Command::Remove { word, yes } => {
+            let store = VocabStore::from(&db)?;
+            for (_, translation) in find_word(&store, &word)? {
+                let question = format!("Remove {} - {}?", translation.local, translation.foreign);
+                if yes || confirm(&question)? {
+                    store.remove(&translation)?;
+                    println!("removed: {} - {}", translation.local, translation.foreign);
+                }
+            }
+        }
+
+        Command::Edit {
+            word,
+            local,
+            foreign,
+            details,
+            yes,
+        } => {
+            let store = VocabStore::from(&db)?;
+            let deck = store.current_deck()?;
+            let renaming = local.is_some() || foreign.is_some();
+            let translations = find_word(&store, &word)?;
+            // Every match would be renamed to the same words, which only one of them can have
+            if renaming && translations.len() > 1 {
+                return Err(AppError::AmbiguousWord(
+                    word,
+                    translations
+                        .into_iter()
+                        .map(|(_, t)| (t.local, t.foreign))
+                        .collect(),
+                ));
+            }
+            // Asked before the transaction starts so the database isn't locked while waiting
+            let mut confirmed = Vec::new();
+            for (_, translation) in translations {
+                let new_local = local.as_deref().unwrap_or(&translation.local);
+                let new_foreign = foreign.as_deref().unwrap_or(&translation.foreign);
+                let question = if renaming {
+                    format!(
+                        "Change {} - {} to {} - {}?",
+                        translation.local, translation.foreign, new_local, new_foreign
+                    )
+                } else {
+                    format!("Update {} - {}?", translation.local, translation.foreign)
+                };
+                // Applied before anything is changed so a bad detail doesn't leave a half edit
+                let mut edited = translation.clone();
+                details.apply(&mut edited, &deck)?;
+                if yes || confirm(&question)? {
+                    confirmed.push((translation, edited));
+                }
+            }
+            // All or nothing, so a failure part way doesn't leave a half edit
+            let updated = store.transaction(|| {
+                let mut updated = Vec::new();
+                for (translation, mut edited) in confirmed {
+                    if renaming {
+                        let new_local = local.as_deref().unwrap_or(&translation.local);
+                        let new_foreign = foreign.as_deref().unwrap_or(&translation.foreign);
+                        let renamed = match store.rename(&translation, new_local, new_foreign) {
+                            Err(VocabStoreError::DuplicateEntry) => {
+                                return Err(AppError::TranslationExists(
+                                    new_local.to_string(),
+                                    new_foreign.to_string(),
+                                ))
+                            }
+                            result => result?,
+                        };
+                        edited.local = renamed.local;
+                        edited.foreign = renamed.foreign;
+                    }
+                    if !details.is_empty() {
+                        store.save(&edited)?;
+                    }
+                    updated.push(edited);
+                }
+                Ok(updated)
+            })?;
+            for translation in updated {
+                println!("updated: {} - {}", translation.local, translation.foreign);
+            }
+        }
+
+        Command::Alt(alt_command) => {
+            let store = VocabStore::from(&db)?;
+            let (word, alternate, add) = match alt_command {
+                AltCommand::Add { word, alternate } => (word, alternate, true),
+                AltCommand::Remove { word, alternate } => (word, alternate, false),
+            };
+            if add {
+                parse_answers(&alternate)?;
+            }
+            for (side, mut translation) in find_word(&store, &word)? {
+                let alternates = translation.alternates_mut(side);
+                let changed = if add {
+                    alternates.add(&alternate)
+                } else {
+                    alternates.remove(&alternate)
+                };
+                if changed {
+                    store.save(&translation)?;
+                }
+                println!(
+                    "{} - {}: {}",
+                    translation.local,
+                    translation.foreign,
+                    list_answers(&translation.answers(side))
+                );
+            }
         }
 
-        Command::Add { local, foreign } => {
-            let translation = Translation::new(&local, &foreign);
-            VocabStore::from(SQLITE_FILE)?.add(&translation)?;
+        Command::Tag(tag_command) => {
+            let store = VocabStore::from(&db)?;
+            match tag_command {
+                TagCommand::Add { word, tag } => {
+                    for (_, translation) in find_word(&store, &word)? {
+                        store.add_tag(&translation, &tag)?;
+                        print_tags(&store, &translation)?;
+                    }
+                }
+                TagCommand::Remove { word, tag } => {
+                    for (_, translation) in find_word(&store, &word)? {
+                        store.remove_tag(&translation, &tag)?;
+                        print_tags(&store, &translation)?;
+                    }
+                }
+                TagCommand::List { word: Some(word) } => {
+                    for (_, translation) in find_word(&store, &word)? {
+                        print_tags(&store, &translation)?;
+                    }
+                }
+                TagCommand::List { word: None } => {
+                    for tag in store.tags()? {
+                        println!("{} ({})", tag.name, tag.translations);
+                    }
+                }
+            }
         }
 
-        Command::Single => {
-            let store = VocabStore::from(SQLITE_FILE)?;
-            if let Some(guess_result) = store.guesses().next() {
+        Command::Deck(deck_command) => {
+            let mut store = VocabStore::from(&db)?;
+            match deck_command {
+                DeckCommand::Create {
+                    name,
+                    local,
+                    foreign,
+                } => {
+                    let deck = store.create_deck(&name, &local, &foreign)?;
+                    println!(
+                        "Created {}, switch to it with `vocab deck use {}`",
+                        deck.name, deck.name
+                    );
+                }
+                DeckCommand::List => {
+                    let current = store.current_deck()?;
+                    for deck in store.decks()? {
+                        let marker = if deck.id == current.id { "*" } else { " " };
+                        let mut line = format!("{} {}", marker, deck.name);
+                        if !deck.local_language.is_empty() || !deck.foreign_language.is_empty() {
+                            line.push_str(&format!(
+                                " ({} - {})",
+                                deck.local_language, deck.foreign_language
+                            ));
+                        }
+                        println!("{}, {} words", line, store.deck_size(&deck)?);
+                    }
+                }
+                DeckCommand::Use { name } => {
+                    let deck = store.use_deck(&name)?;
+                    println!("Using {}", deck.name);
+                }
+                DeckCommand::Delete { name, yes } => {
+                    let deck = store
+                        .find_deck(&name)?
+                        .ok_or(VocabStoreError::DeckNotFound)?;
+                    if deck.id == store.current_deck()?.id {
+                        return Err(VocabStoreError::DeckInUse.into());
+                    }
+                    let question = format!(
+                        "Delete {} and its {} words?",
+                        deck.name,
+                        store.deck_size(&deck)?
+                    );
+                    if !yes && !confirm(&question)? {
+                        return Ok(());
+                    }
+                    store.delete_deck(&deck.name)?;
+                    println!("Deleted {}", deck.name);
+                }
+                DeckCommand::Set {
+                    show_reading,
+                    near_miss,
+                    ignore_diacritics,
+                    case_sensitive,
+                    locale,
+                    transliteration,
+                    overrule_wrong,
+                } => {
+                    let mut deck = store.current_deck()?;
+                    let original = deck.clone();
+                    if let Some(show_reading) = show_reading {
+                        deck.show_reading = show_reading;
+                    }
+                    if let Some(near_miss) = near_miss {
+                        deck.near_miss = near_miss;
+                    }
+                    if let Some(ignore_diacritics) = ignore_diacritics {
+                        deck.ignore_diacritics = ignore_diacritics;
+                    }
+                    if let Some(case_sensitive) = case_sensitive {
+                        deck.case_sensitive = case_sensitive;
+                    }
+                    if let Some(locale) = locale {
+                        deck.locale = locale.trim().to_string();
+                    }
+                    if let Some(overrule_wrong) = overrule_wrong {
+                        deck.overrule_wrong = overrule_wrong;
+                    }
+                    match transliteration.as_deref() {
+                        None => {}
+                        Some("none") => deck.transliteration = None,
+                        Some(scheme) => {
+                            deck.transliteration =
+                                Some(scheme.parse().map_err(AppError::InvalidDetail)?)
+                        }
+                    }
+                    if deck != original {
+                        store.save_deck(&deck)?;
+                    }
+                    println!("{}", deck.name);
+                    println!("  show-reading: {}", deck.show_reading);
+                    println!("  near-miss: {}", deck.near_miss);
+                    println!("  ignore-diacritics: {}", deck.ignore_diacritics);
+                    println!("  case-sensitive: {}", deck.case_sensitive);
+                    println!("  locale: {}", deck.locale);
+                    match deck.transliteration {
+                        Some(transliteration) => println!("  transliteration: {}", transliteration),
+                        None => println!("  transliteration: none"),
+                    }
+                    println!("  overrule-wrong: {}", deck.overrule_wrong);
+                }
+            }
+        }
+
+        Command::Single { strategy, tag } => {
+            let store = VocabStore::from(&db)?;
+            let deck = store.current_deck()?;
+            if let Some(guess_result) = store
+                .guesses(strategy.selection_strategy())
+                .with_tag(tag.tag.as_deref())
+                .next()
+            {
                 let mut guess = guess_result?;
-                let result = handle_guess(&mut guess)?;
-                store.save(&guess)?;
-                return if result {
+                let result = handle_guess(&deck, &mut guess);
+                // Quitting after answering, eg instead of overruling, still saves the answer
+                if !guess.attempts().is_empty() {
+                    store.save_guess(&mut guess)?;
+                }
+                return if result? {
                     Ok(())
                 } else {
                     Err(AppError::IncorrectGuessInSingleMode)
@@ -213,36 +1276,117 @@ fn app() -> Result<(), AppError> {
             return Err(AppError::NoTranslationsFound);
         }
 
-        Command::Endless => {
-            let store = VocabStore::from(SQLITE_FILE)?;
-            for guess_result in store.guesses() {
-                let mut guess = guess_result?;
-                handle_guess(&mut guess)?;
-                store.save(&guess)?;
+        Command::Endless {
+            strategy,
+            tag,
+            choices,
+        } => {
+            let store = VocabStore::from(&db)?;
+            let deck = store.current_deck()?;
+            let guesses = store
+                .guesses(strategy.selection_strategy())
+                .with_tag(tag.tag.as_deref())
+                .with_choices(choices.unwrap_or(0));
+            if !practise(&store, &deck, guesses, handle_guess)? {
+                return Err(AppError::NoTranslationsFound);
+            }
+        }
+
+        Command::Review => {
+            let store = VocabStore::from(&db)?;
+            let deck = store.current_deck()?;
+            if practise(&store, &deck, store.guesses(DueForReview), handle_guess)? {
+                return Ok(());
+            }
+            match store.next_due()? {
+                None => return Err(AppError::NoTranslationsFound),
+                Some(next_due) => {
+                    println!("Nothing left to review");
+                    println!(
+                        "The next word is due {}",
+                        DateTime::<Utc>::from_naive_utc_and_offset(next_due, Utc)
+                            .with_timezone(&Local)
+                            .format("%Y-%m-%d %H:%M")
+                    );
+                }
+            }
+        }
+
+        Command::Flash { strategy, tag } => {
+            let store = VocabStore::from(&db)?;
+            let deck = store.current_deck()?;
+            let guesses = store
+                .guesses(strategy.selection_strategy())
+                .with_tag(tag.tag.as_deref());
+            if !practise(&store, &deck, guesses, handle_flash)? {
+                return Err(AppError::NoTranslationsFound);
             }
-            return Err(AppError::NoTranslationsFound);
         }
 
-        Command::Export { file } => {
-            let store = VocabStore::from(SQLITE_FILE)?;
+        Command::Genders { tag } => {
+            let store = VocabStore::from(&db)?;
+            let deck = store.current_deck()?;
+            // A gender the language doesn't have can't be answered, eg a common German noun
+            let (words, skipped): (Vec<_>, Vec<_>) = store
+                .find_gendered(tag.tag.as_deref())?
+                .into_iter()
+                .partition(|t| t.gender.is_some_and(|g| g.in_language(deck.language())));
+            for translation in &skipped {
+                println!(
+                    "Skipping {}, nouns can't be {} in this deck's language",
+                    translation.foreign,
+                    translation.gender.expect("only gendered words are found")
+                );
+            }
+            if words.is_empty() {
+                return Err(AppError::NoTranslationsFound);
+            }
+            start_session();
+            let mut rng = rand::thread_rng();
+            while let Some(word) = words.choose(&mut rng) {
+                let correct = match handle_gender(&deck, word) {
+                    Err(AppError::Quit) => break,
+                    result => result?,
+                };
+                let outcome = if correct {
+                    Outcome::Correct
+                } else {
+                    Outcome::Incorrect
+                };
+                if let Some(session) = lock_session().as_mut() {
+                    session.record(word, outcome);
+                }
+            }
+            end_session();
+        }
+
+        Command::Export { file, tag } => {
+            let store = VocabStore::from(&db)?;
             let write: Box<dyn Write> = match file.as_deref().unwrap_or("-") {
                 "-" => Box::new(io::stdout()),
                 f => {
                     if Path::new(f).exists() {
                         return Err(AppError::ExportFileAlreadyExists);
                     }
-                    Box::new(fs::OpenOptions::new().create(true).write(true).open(f)?)
+                    Box::new(
+                        fs::OpenOptions::new()
+                            .create_new(true)
+                            .write(true)
+                            .open(f)?,
+                    )
                 }
             };
             let mut csv_writer = CsvWriter::new(write);
 
-            for record in store.entries() {
-                csv_writer.write(record?)?;
+            for record in store.entries().with_tag(tag.tag.as_deref()) {
+                let translation = record?;
+                let tags = store.tags_of(&translation)?;
+                csv_writer.write(translation, &tags)?;
             }
         }
 
         Command::Import { file } => {
-            let store = VocabStore::from(SQLITE_FILE)?;
+            let store = VocabStore::from(&db)?;
             let read: Box<dyn Read> = match file.as_deref().unwrap_or("-") {
                 "-" => Box::new(io::stdin()),
                 f => {
@@ -255,43 +1399,621 @@ fn app() -> Result<(), AppError> {
             let csv_reader = CsvReader::new(read);
 
             for record in csv_reader {
-                let new_t = record?;
-                if let Some(old_t) = store.find_local(&new_t.local)? {
+                let (new_t, tags) = record?;
+                // Match on uuid first so translations renamed since the export are still found
+                let old_t = match store.find_by_uuid(&new_t.uuid)? {
+                    Some(old_t) => Some(old_t),
+                    None => store.find(&new_t.local, &new_t.foreign)?,
+                };
+                let stored = if let Some(old_t) = old_t {
+                    let old_words = format!("{} - {}", &old_t.local, &old_t.foreign);
                     let rec_t = old_t.reconcile(new_t)?;
                     store.save(&rec_t)?;
-                    println!("updated: {} - {}", &rec_t.local, &rec_t.foreign);
+                    let new_words = format!("{} - {}", &rec_t.local, &rec_t.foreign);
+                    if old_words == new_words {
+                        println!("updated: {}", new_words);
+                    } else {
+                        println!("renamed: {} to {}", old_words, new_words);
+                    }
+                    Some(rec_t)
                 } else {
                     store.add(&new_t)?;
                     println!("added:   {} - {}", &new_t.local, &new_t.foreign);
+                    store.find(&new_t.local, &new_t.foreign)?
+                };
+                // Tags in the file are added to the ones the translation already has
+                if let Some(translation) = stored {
+                    for tag in &tags {
+                        store.add_tag(&translation, tag)?;
+                    }
                 }
             }
         }
+
+        Command::Migrate { status } => {
+            let before = VocabStore::migration_status(&db)?;
+            if status {
+                print_migration_status(&before);
+                return Ok(());
+            }
+            if before.is_too_new() || before.is_up_to_date() {
+                VocabStore::from(&db)?;
+                println!("Database is up to date (version {})", before.current);
+                return Ok(());
+            }
+            VocabStore::from(&db)?;
+            for name in &before.pending {
+                println!("applied: {}", name);
+            }
+            print!(
+                "Database upgraded from version {} to {}",
+                before.current, before.latest
+            );
+            if before.needs_backup() {
+                print!(", a backup of the old database was kept");
+            }
+            println!();
+        }
     };
     Ok(())
 }
 
-fn handle_guess(guess: &mut Guess) -> Result<bool, AppError> {
+/// How a practice session went, summarised when it ends
+struct Session {
+    started: Instant,
+    seen: usize,
+    correct: usize,
+    /// Answered correctly, but only after a hint
+    hinted: usize,
+    missed: Vec<String>,
+    /// Whether an answer has been given that hasn't been saved yet
+    unsaved: bool,
+}
+
+impl Session {
+    fn new() -> Session {
+        Session {
+            started: Instant::now(),
+            seen: 0,
+            correct: 0,
+            hinted: 0,
+            missed: Vec::new(),
+            unsaved: false,
+        }
+    }
+
+    fn record(&mut self, translation: &Translation, outcome: Outcome) {
+        self.unsaved = false;
+        self.seen += 1;
+        if outcome == Outcome::Correct {
+            self.correct += 1;
+        } else if outcome == Outcome::Hinted {
+            self.hinted += 1;
+        } else {
+            let word = format!("{} - {}", translation.local, translation.foreign);
+            if !self.missed.contains(&word) {
+                self.missed.push(word);
+            }
+        }
+    }
+
+    fn summarise(&self) {
+        if self.seen == 0 {
+            return;
+        }
+        let seconds = self.started.elapsed().as_secs();
+        println!();
+        println!("Words seen: {}", self.seen);
+        println!(
+            "Accuracy: {:.0}%",
+            self.correct as f64 / self.seen as f64 * 100.0
+        );
+        if self.hinted > 0 {
+            println!("With hints: {}", self.hinted);
+        }
+        if !self.missed.is_empty() {
+            println!("Missed: {}", self.missed.join(", "));
+        }
+        println!("Time spent: {}m {}s", seconds / 60, seconds % 60);
+    }
+}
+
+/// The session in progress, shared with the Ctrl+C handler so it can be summarised
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+fn lock_session() -> MutexGuard<'static, Option<Session>> {
+    SESSION.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn start_session() {
+    *lock_session() = Some(Session::new());
+}
+
+fn end_session() {
+    if let Some(session) = lock_session().take() {
+        session.summarise();
+    }
+}
+
+/// Ends the session on Ctrl+C with its summary, or exits as usual outside of a session. The
+/// handler waits for an answer that is being saved, but can't wait for the user to finish with a
+/// prompt, so a card that hasn't been answered is never saved and nor is an answer that is waiting
+/// to be overruled. `:q` at that prompt keeps the answer.
+fn handle_ctrl_c() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(|| {
+        println!();
+        match lock_session().take() {
+            Some(session) => {
+                if session.unsaved {
+                    println!("Your last answer wasn't saved");
+                }
+                session.summarise();
+                std::process::exit(0);
+            }
+            None => std::process::exit(130),
+        }
+    })
+}
+
+/// Asks each guess in turn until they run out or the user quits, then summarises the session.
+/// Returns true if the user quit.
+fn practise<I>(
+    store: &VocabStore,
+    deck: &Deck,
+    guesses: I,
+    handle: fn(&Deck, &mut Guess) -> Result<bool, AppError>,
+) -> Result<bool, AppError>
+where
+    I: IntoIterator<Item = Result<Guess, VocabStoreError>>,
+{
+    start_session();
+    let mut quit = false;
+    for guess_result in guesses {
+        let mut guess = guess_result?;
+        match handle(deck, &mut guess) {
+            Err(AppError::Quit) => quit = true,
+            result => {
+                result?;
+            }
+        }
+        // Quitting after answering, eg instead of overruling, still saves the answer
+        if let Some(outcome) = guess.attempts().last().map(|attempt| attempt.outcome) {
+            let mut session = lock_session();
+            store.save_guess(&mut guess)?;
+            if let Some(session) = session.as_mut() {
+                session.record(&guess, outcome);
+            }
+        }
+        if quit {
+            break;
+        }
+    }
+    end_session();
+    Ok(quit)
+}
+
+/// There is nothing to choose between with fewer than two options
+fn at_least_two(choices: String) -> Result<(), String> {
+    match choices.parse::<usize>() {
+        Ok(choices) if choices >= 2 => Ok(()),
+        _ => Err(String::from("there must be at least 2 choices")),
+    }
+}
+
+/// Typed in place of an answer to ask for a hint
+const HINT: &str = "?";
+
+/// Whether the reading is shown with the prompt rather than the answer. The reading is only ever
+/// given for the foreign word, which is shown when asking for the local one.
+fn reading_in_prompt(deck: &Deck, guess: &Guess) -> bool {
+    deck.show_reading == ShowReading::Prompt && guess.direction() == Direction::Local
+}
+
+fn show_prompt(deck: &Deck, guess: &Guess) {
+    let language = match guess.direction() {
+        Direction::Local => &deck.local_language,
+        Direction::Foreign => &deck.foreign_language,
+    };
+    let reading_in_prompt = reading_in_prompt(deck, guess);
+    let mut prompt = match guess.direction() {
+        Direction::Local => with_article(guess, deck.language()),
+        Direction::Foreign => guess.render().to_string(),
+    };
+    if let (Some(reading), true) = (&guess.reading, reading_in_prompt) {
+        prompt.push_str(&format!(" ({})", reading));
+    }
+    // The gender and plural would give the answer away when asking for the foreign word
+    if let Some(grammar) = grammar(guess, guess.direction() == Direction::Local) {
+        prompt.push_str(&format!(" ({})", grammar));
+    }
+    println!();
+    if language.is_empty() {
+        println!("Translate: {}", prompt);
+    } else {
+        println!("Translate to {}: {}", language, prompt);
+    }
+}
+
+/// The details that weren't in the prompt, shown with the answer
+fn show_details(deck: &Deck, guess: &Guess) {
+    if let (Some(reading), false) = (&guess.reading, reading_in_prompt(deck, guess)) {
+        println!("Reading: {}", reading);
+    }
+    if guess.direction() == Direction::Foreign && (guess.gender.is_some() || guess.plural.is_some())
+    {
+        let foreign = with_article(guess, deck.language());
+        match grammar(guess, true) {
+            Some(grammar) => println!("{} ({})", foreign, grammar),
+            None => println!("{}", foreign),
+        }
+    }
+}
+
+fn handle_guess(deck: &Deck, guess: &mut Guess) -> Result<bool, AppError> {
+    show_prompt(deck, guess);
+    for (number, choice) in guess.choices().iter().enumerate() {
+        println!("  {}) {}", number + 1, choice);
+    }
+    let (user_guess, chosen) = loop {
+        write_stdout("Your guess: ")?;
+        let input = read_stdin()?;
+        // Options can be chosen by their number or typed out
+        let chosen = input
+            .parse::<usize>()
+            .ok()
+            .map(|number| number.wrapping_sub(1))
+            .filter(|index| *index < guess.choices().len());
+        if let Some(index) = chosen {
+            break (guess.choices()[index].clone(), chosen);
+        }
+        if input != HINT {
+            break (input, None);
+        }
+        match guess.hint() {
+            Some(hint) => println!("Hint: {}", hint),
+            None => println!("That's the whole answer"),
+        }
+    };
+    // A chosen option is only right if it is the right one, not if it is close to it
+    let correct = match chosen {
+        Some(index) => guess.choose(index),
+        None => guess.guess(&user_guess),
+    };
+    let (outcome, matched) = match guess.attempts().last() {
+        Some(attempt) => (attempt.outcome, attempt.matched.clone()),
+        None => (Outcome::Incorrect, Match::Wrong),
+    };
+    let near_miss = matches!(matched, Match::Close { .. });
+    let typed = user_guess.trim();
+    match (outcome, matched) {
+        (Outcome::Correct, Match::Transliterated { expected }) => {
+            println!("Correct! That's {}", expected)
+        }
+        (Outcome::Correct, Match::Diacritics { expected }) => {
+            println!("Correct! Watch the accents, it's {}", expected)
+        }
+        (Outcome::Correct, Match::Close { expected }) => {
+            println!(
+                "Correct! Close enough, you typed {}, expected {}",
+                typed, expected
+            )
+        }
+        (Outcome::Correct, _) => println!("Correct!"),
+        (Outcome::Hinted, _) => match guess.hints() {
+            1 => println!("Correct, with a hint"),
+            hints => println!("Correct, with {} hints", hints),
+        },
+        (Outcome::Typo, Match::Close { expected }) => {
+            println!("Almost, you typed {}, expected {}", typed, expected);
+            show_diff(typed, &expected);
+        }
+        (_, matched) => {
+            if let Match::Close { expected } = &matched {
+                println!("Close, you typed {}, expected {}", typed, expected);
+            }
+            println!(
+                "Incorrect! The actual translation is {}",
+                list_answers(&guess.accepted_answers())
+            );
+            let closest = match matched {
+                Match::Close { expected } => Some(expected),
+                _ => closest_answer(typed, &guess.accepted_answers()),
+            };
+            if let Some(expected) = closest {
+                show_diff(typed, &expected);
+            }
+        }
+    }
+    show_details(deck, guess);
+    if outcome == Outcome::Incorrect {
+        if let Some(note) = &guess.notes {
+            println!("Note: {}", note);
+        }
+        if let Some(mnemonic) = &guess.mnemonic {
+            println!("Remember: {}", mnemonic);
+        }
+        for example in guess.examples.iter() {
+            println!("Example: {}", example);
+        }
+    }
+    // Asking after every wrong answer would hold up practice, so unless the deck wants it the
+    // question is only asked when the answer was nearly right
+    if matches!(outcome, Outcome::Incorrect | Outcome::Typo)
+        && (near_miss || deck.overrule_wrong)
+        && guess.choices().is_empty()
+        && !typed.is_empty()
+    {
+        return overrule(guess, typed);
+    }
+    Ok(correct)
+}
+
+/// Typed after a wrong answer to say it was right after all
+const OVERRULE: &str = "!";
+
+/// Lets the user overrule the grading of a wrong answer, and accept it from then on
+fn overrule(guess: &mut Guess, typed: &str) -> Result<bool, AppError> {
+    if let Some(session) = lock_session().as_mut() {
+        session.unsaved = true;
+    }
+    write_stdout(&format!(
+        "Press Enter to carry on, or type {} if you were right: ",
+        OVERRULE
+    ))?;
+    if read_stdin()? != OVERRULE || !guess.overrule() {
+        return Ok(false);
+    }
+    println!("Marked as correct");
+    let question = format!("Accept {} as an answer for {}?", typed, guess.render());
+    if parse_answers(typed).is_ok() && confirm(&question)? && guess.add_alternate(typed) {
+        println!("{} will be accepted from now on", typed);
+    }
+    Ok(guess
+        .attempts()
+        .last()
+        .is_some_and(|attempt| attempt.correct))
+}
+
+/// Answers for showing, with any written as `car; automobile` listed separately
+fn list_answers(answers: &[&str]) -> String {
+    answers
+        .iter()
+        .flat_map(|answer| choices(answer))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Lines up a typed answer with the expected one, colouring the differences, or marking them
+/// with `^` when the output isn't a terminal. Nothing is shown unless at least half of the
+/// expected answer was typed, as lining up unrelated words doesn't help.
+fn show_diff(typed: &str, expected: &str) {
+    let differences = diff(typed, expected);
+    let same = differences
+        .iter()
+        .filter(|difference| matches!(difference, Difference::Same { .. }))
+        .count();
+    let expected_length = differences
+        .iter()
+        .filter(|difference| !matches!(difference, Difference::Extra(_)))
+        .count();
+    if same == 0 || same * 2 < expected_length {
+        return;
+    }
+    let colour = use_colour();
+    let mut typed_line = String::new();
+    let mut expected_line = String::new();
+    let mut markers = String::new();
+    for difference in &differences {
+        let (typed, expected) = match difference {
+            Difference::Same { typed, expected } | Difference::Changed { typed, expected } => {
+                (typed.as_str(), expected.as_str())
+            }
+            Difference::Missing(expected) => ("_", expected.as_str()),
+            Difference::Extra(typed) => (typed.as_str(), ""),
+        };
+        let width = typed.width().max(expected.width());
+        let pad = |text: &str| " ".repeat(width - text.width());
+        if let Difference::Same { .. } = difference {
+            typed_line.push_str(&format!("{}{}", typed, pad(typed)));
+            expected_line.push_str(&format!("{}{}", expected, pad(expected)));
+            markers.push_str(&" ".repeat(width));
+        } else if colour {
+            typed_line.push_str(&format!("{}{}", paint(AnsiColor::Red, typed), pad(typed)));
+            expected_line.push_str(&format!(
+                "{}{}",
+                paint(AnsiColor::Green, expected),
+                pad(expected)
+            ));
+        } else {
+            typed_line.push_str(&format!("{}{}", typed, pad(typed)));
+            expected_line.push_str(&format!("{}{}", expected, pad(expected)));
+            markers.push_str(&"^".repeat(width));
+        }
+    }
+    println!("  You typed: {}", typed_line);
+    println!("  Expected:  {}", expected_line.trim_end());
+    if !colour {
+        println!("             {}", markers.trim_end());
+    }
+}
+
+/// Colour is only used in a terminal, and never if `NO_COLOR` is set to anything
+// is_none_or would need Rust 1.82, which nothing else here does
+#[allow(clippy::unnecessary_map_or)]
+fn use_colour() -> bool {
+    io::stdout().is_terminal() && env::var_os("NO_COLOR").map_or(true, |v| v.is_empty())
+}
+
+fn paint(colour: AnsiColor, text: &str) -> String {
+    let style = Style::new().fg_color(Some(colour.into()));
+    format!("{}{}{}", style.render(), text, style.render_reset())
+}
+
+fn handle_flash(deck: &Deck, guess: &mut Guess) -> Result<bool, AppError> {
+    show_prompt(deck, guess);
+    write_stdout("Press Enter to see the answer")?;
+    read_stdin()?;
+    println!("Answer: {}", guess.render_translation());
+    show_details(deck, guess);
+    let grade = loop {
+        write_stdout("How well did you remember it? again, hard, good or easy [good] ")?;
+        let input = read_stdin()?;
+        if input.is_empty() {
+            break Grade::Good;
+        }
+        match input.parse() {
+            Ok(grade) => break grade,
+            Err(message) => println!("{}", message),
+        }
+    };
+    Ok(guess.grade(grade))
+}
+
+fn handle_gender(deck: &Deck, translation: &Translation) -> Result<bool, AppError> {
+    let gender = match translation.gender {
+        Some(gender) => gender,
+        None => return Ok(false),
+    };
+    let options: Vec<String> = match Gender::articles(deck.language()) {
+        Some(articles) => articles.iter().map(|(_, a)| a.to_string()).collect(),
+        None => vec!["masculine", "feminine", "neuter", "common"]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+    };
+    let (last, rest) = options.split_last().expect("there is always a gender");
     println!();
-    println!("Translate: {}", guess.render());
+    println!("{} or {}? {}", rest.join(", "), last, translation.foreign);
     write_stdout("Your guess: ")?;
     let user_guess = read_stdin()?;
-    if guess.guess(&user_guess) {
+    let correct = gender.accepts(&user_guess, deck.language());
+    if correct {
         println!("Correct!");
-        Ok(true)
     } else {
-        println!(
-            "Incorrect! The actual translation is {}",
-            guess.render_translation()
-        );
-        Ok(false)
+        match gender.article(deck.language()) {
+            Some(_) => println!(
+                "Incorrect! It's {}",
+                with_article(translation, deck.language())
+            ),
+            None => println!("Incorrect! It's {}", gender),
+        }
     }
+    Ok(correct)
 }
 
+/// The foreign word with its definite article, eg `das haus`, when its gender is known and vocab
+/// knows the articles of the deck's language
+fn with_article(translation: &Translation, language: &str) -> String {
+    match translation.gender.and_then(|g| g.article(language)) {
+        Some(article) => format!("{} {}", article, translation.foreign),
+        None => translation.foreign.clone(),
+    }
+}
+
+/// The part of speech of a word and, if `foreign` is set, the gender and plural of the foreign
+/// word, eg `noun, neuter, plural häuser`
+fn grammar(translation: &Translation, foreign: bool) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(part_of_speech) = translation.part_of_speech {
+        parts.push(part_of_speech.to_string());
+    }
+    if foreign {
+        if let Some(gender) = translation.gender {
+            parts.push(gender.to_string());
+        }
+        if let Some(plural) = &translation.plural {
+            parts.push(format!("plural {}", plural));
+        }
+    }
+    Some(parts.join(", ")).filter(|p| !p.is_empty())
+}
+
+/// Where the database is. `init` creates it in the current directory unless told otherwise, every
+/// other command looks for the nearest one in the current directory or its parents before falling
+/// back to the global database.
+fn database_path(db: Option<PathBuf>, command: &Command) -> Result<String, AppError> {
+    let path = match (db, command) {
+        (Some(db), _) => db,
+        (None, Command::Init { global: true }) => {
+            global_database().ok_or(AppError::NoDataDirectory)?
+        }
+        (None, Command::Init { global: false }) => PathBuf::from(DATABASE_FILE),
+        (None, _) => find_database(&env::current_dir()?)
+            .or_else(global_database)
+            .ok_or(AppError::NoDataDirectory)?,
+    };
+    path.into_os_string()
+        .into_string()
+        .map_err(|_| AppError::InvalidDatabasePath)
+}
+
+/// Every translation of a word, looking at local words first and then foreign ones, along with
+/// which side of the translation the word is on
+fn find_word(store: &VocabStore, word: &str) -> Result<Vec<(Direction, Translation)>, AppError> {
+    let mut translations: Vec<(Direction, Translation)> = store
+        .find_local(word)?
+        .into_iter()
+        .map(|t| (Direction::Local, t))
+        .collect();
+    if translations.is_empty() {
+        translations = store
+            .find_foreign(word)?
+            .into_iter()
+            .map(|t| (Direction::Foreign, t))
+            .collect();
+    }
+    if translations.is_empty() {
+        return Err(AppError::WordNotFound(word.to_string()));
+    }
+    Ok(translations)
+}
+
+fn print_tags(store: &VocabStore, translation: &Translation) -> Result<(), AppError> {
+    println!(
+        "{} - {}: {}",
+        translation.local,
+        translation.foreign,
+        store.tags_of(translation)?.join(", ")
+    );
+    Ok(())
+}
+
+fn print_migration_status(status: &MigrationStatus) {
+    println!("Schema version: {}", status.current);
+    println!("Latest version: {}", status.latest);
+    if status.is_too_new() {
+        println!("The database is newer than this version of vocab");
+    } else if status.pending.is_empty() {
+        println!("No pending migrations");
+    } else {
+        println!("Pending migrations:");
+        for name in &status.pending {
+            println!("    {}", name);
+        }
+    }
+}
+
+/// Asks a yes or no question, anything other than yes is taken as no
+fn confirm(question: &str) -> Result<bool, AppError> {
+    write_stdout(&format!("{} [y/N] ", question))?;
+    Ok(matches!(read_stdin()?.to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Typed at any prompt to stop
+const QUIT: &str = ":q";
+
+/// Reads a line of input, or `AppError::Quit` if the user typed `:q` or there is no more input
 fn read_stdin() -> Result<String, AppError> {
     let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-
-    Ok(input.trim().to_string())
+    if io::stdin().read_line(&mut input)? == 0 {
+        println!();
+        return Err(AppError::Quit);
+    }
+    let input = input.trim();
+    if input == QUIT {
+        return Err(AppError::Quit);
+    }
+    Ok(input.to_string())
 }
 
 fn write_stdout(output: &str) -> Result<(), AppError> {
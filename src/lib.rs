@@ -1,8 +1,19 @@
+// The diesel 1.4 macros generate their impls inside of functions and constants
+#![allow(non_local_definitions)]
+
 #[macro_use]
 extern crate diesel;
 
 pub use porter::{CsvReader, CsvWriter, ExporterError};
-pub use vocab_store::{Guess, Translation, VocabStore, VocabStoreError};
+pub use vocab_store::{
+    check_answer, choices, closest_answer, diff, edit_distance, find_database, fold_case,
+    global_database, parse_answers, variants, Alternates, Attempt, Choice, Cyrillic, Deck,
+    Difference, Direction, DueForReview, Examples, Gender, Grade, Greek, Guess, Hangul,
+    LeastRecentlyReviewed, Match, Matcher, MigrationStatus, NearMiss, NewWordsFirst, Outcome,
+    PartOfSpeech, Review, Romaji, SelectionStrategy, ShowReading, Tag, Translation, Transliterate,
+    Transliteration, UniformRandom, VocabStore, VocabStoreError, WeakestFirst, WeightedRandom,
+    DATABASE_FILE, MAX_OPTIONAL_PARTS,
+};
 
 mod porter;
 mod schema;
@@ -0,0 +1,2 @@
+ALTER TABLE translations ADD COLUMN "local_alternates" VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE translations ADD COLUMN "foreign_alternates" VARCHAR NOT NULL DEFAULT '';
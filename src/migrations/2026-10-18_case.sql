@@ -0,0 +1,3 @@
+ALTER TABLE decks ADD COLUMN "case_sensitive" BOOLEAN NOT NULL DEFAULT 0;
+
+ALTER TABLE decks ADD COLUMN "locale" VARCHAR NOT NULL DEFAULT '';
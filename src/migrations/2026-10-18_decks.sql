@@ -0,0 +1,44 @@
+CREATE TABLE decks
+(
+    "id"               INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "name"             VARCHAR NOT NULL UNIQUE,
+    "local_language"   VARCHAR NOT NULL DEFAULT '',
+    "foreign_language" VARCHAR NOT NULL DEFAULT ''
+);
+
+INSERT INTO decks ("id", "name")
+VALUES (1, 'default');
+
+CREATE TABLE translations_with_deck
+(
+    "id"                      INTEGER          NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "deck_id"                 INTEGER          NOT NULL REFERENCES decks ("id") ON DELETE CASCADE,
+    "local"                   VARCHAR          NOT NULL,
+    "foreign"                 VARCHAR          NOT NULL,
+    "guesses_local_total"     UNSIGNED INTEGER NOT NULL,
+    "guesses_local_correct"   UNSIGNED INTEGER NOT NULL,
+    "guesses_foreign_total"   UNSIGNED INTEGER NOT NULL,
+    "guesses_foreign_correct" UNSIGNED INTEGER NOT NULL,
+    "ease_factor"             REAL             NOT NULL DEFAULT 2.5,
+    "interval_days"           INTEGER          NOT NULL DEFAULT 0,
+    "repetitions"             INTEGER          NOT NULL DEFAULT 0,
+    "lapses"                  INTEGER          NOT NULL DEFAULT 0,
+    "due_at"                  TIMESTAMP        NULL,
+    "last_reviewed_at"        TIMESTAMP        NULL,
+    "local_alternates"        VARCHAR          NOT NULL DEFAULT '',
+    "foreign_alternates"      VARCHAR          NOT NULL DEFAULT '',
+    unique ("deck_id", "local", "foreign")
+);
+
+INSERT INTO translations_with_deck
+SELECT "id", 1, "local", "foreign", "guesses_local_total", "guesses_local_correct", "guesses_foreign_total",
+       "guesses_foreign_correct", "ease_factor", "interval_days", "repetitions", "lapses", "due_at",
+       "last_reviewed_at", "local_alternates", "foreign_alternates"
+FROM translations;
+
+DROP TABLE translations;
+
+ALTER TABLE translations_with_deck RENAME TO translations;
+
+CREATE INDEX translations_due_at ON translations ("deck_id", "due_at");
+CREATE INDEX translations_foreign ON translations ("deck_id", "foreign");
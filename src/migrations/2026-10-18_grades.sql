@@ -0,0 +1,4 @@
+ALTER TABLE reviews ADD COLUMN "grade" VARCHAR NOT NULL DEFAULT 'again';
+
+UPDATE reviews SET "grade" = 'good' WHERE "outcome" = 'correct';
+UPDATE reviews SET "grade" = 'hard' WHERE "outcome" IN ('typo', 'hinted');
@@ -0,0 +1,5 @@
+ALTER TABLE translations ADD COLUMN "part_of_speech" VARCHAR NULL;
+
+ALTER TABLE translations ADD COLUMN "gender" VARCHAR NULL;
+
+ALTER TABLE translations ADD COLUMN "plural" VARCHAR NULL;
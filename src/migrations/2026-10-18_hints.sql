@@ -0,0 +1,11 @@
+ALTER TABLE translations ADD COLUMN "guesses_local_hinted" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE translations ADD COLUMN "guesses_foreign_hinted" INTEGER NOT NULL DEFAULT 0;
+
+UPDATE translations SET "guesses_local_hinted" = (
+    SELECT COUNT(*) FROM reviews
+    WHERE reviews.translation_id = translations.id AND "direction" = 'local' AND "outcome" = 'hinted'
+);
+UPDATE translations SET "guesses_foreign_hinted" = (
+    SELECT COUNT(*) FROM reviews
+    WHERE reviews.translation_id = translations.id AND "direction" = 'foreign' AND "outcome" = 'hinted'
+);
@@ -0,0 +1 @@
+ALTER TABLE decks ADD COLUMN "ignore_diacritics" BOOLEAN NOT NULL DEFAULT 0;
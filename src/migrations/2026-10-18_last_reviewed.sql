@@ -0,0 +1,4 @@
+ALTER TABLE translations ADD COLUMN "last_reviewed_at" TIMESTAMP NULL;
+
+UPDATE translations
+SET "last_reviewed_at" = (SELECT max("reviewed_at") FROM reviews WHERE "translation_id" = translations."id");
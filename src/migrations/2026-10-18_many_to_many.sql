@@ -0,0 +1,30 @@
+CREATE TABLE translations_many_to_many
+(
+    "id"                      INTEGER          NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "local"                   VARCHAR          NOT NULL,
+    "foreign"                 VARCHAR          NOT NULL,
+    "guesses_local_total"     UNSIGNED INTEGER NOT NULL,
+    "guesses_local_correct"   UNSIGNED INTEGER NOT NULL,
+    "guesses_foreign_total"   UNSIGNED INTEGER NOT NULL,
+    "guesses_foreign_correct" UNSIGNED INTEGER NOT NULL,
+    "ease_factor"             REAL             NOT NULL DEFAULT 2.5,
+    "interval_days"           INTEGER          NOT NULL DEFAULT 0,
+    "repetitions"             INTEGER          NOT NULL DEFAULT 0,
+    "lapses"                  INTEGER          NOT NULL DEFAULT 0,
+    "due_at"                  TIMESTAMP        NULL,
+    "last_reviewed_at"        TIMESTAMP        NULL,
+    unique ("local", "foreign")
+);
+
+INSERT INTO translations_many_to_many
+SELECT "id", "local", "foreign", "guesses_local_total", "guesses_local_correct", "guesses_foreign_total",
+       "guesses_foreign_correct", "ease_factor", "interval_days", "repetitions", "lapses", "due_at",
+       "last_reviewed_at"
+FROM translations;
+
+DROP TABLE translations;
+
+ALTER TABLE translations_many_to_many RENAME TO translations;
+
+CREATE INDEX translations_due_at ON translations ("due_at");
+CREATE INDEX translations_foreign ON translations ("foreign");
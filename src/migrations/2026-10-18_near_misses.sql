@@ -0,0 +1,5 @@
+ALTER TABLE decks ADD COLUMN "near_miss" VARCHAR NOT NULL DEFAULT 'wrong';
+
+ALTER TABLE reviews ADD COLUMN "outcome" VARCHAR NOT NULL DEFAULT 'incorrect';
+
+UPDATE reviews SET "outcome" = 'correct' WHERE "correct";
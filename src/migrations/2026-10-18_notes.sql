@@ -0,0 +1,3 @@
+ALTER TABLE translations ADD COLUMN "notes" VARCHAR NULL;
+ALTER TABLE translations ADD COLUMN "examples" VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE translations ADD COLUMN "mnemonic" VARCHAR NULL;
@@ -0,0 +1 @@
+ALTER TABLE decks ADD COLUMN "overrule_wrong" BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+ALTER TABLE translations ADD COLUMN "reading" VARCHAR NULL;
+
+ALTER TABLE decks ADD COLUMN "show_reading" VARCHAR NOT NULL DEFAULT 'answer';
@@ -0,0 +1,36 @@
+CREATE TABLE translations_with_id
+(
+    "id"                      INTEGER          NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "local"                   VARCHAR          NOT NULL,
+    "foreign"                 VARCHAR          NOT NULL,
+    "guesses_local_total"     UNSIGNED INTEGER NOT NULL,
+    "guesses_local_correct"   UNSIGNED INTEGER NOT NULL,
+    "guesses_foreign_total"   UNSIGNED INTEGER NOT NULL,
+    "guesses_foreign_correct" UNSIGNED INTEGER NOT NULL,
+    unique ("local", "foreign"),
+    unique (local),
+    unique ("foreign")
+);
+
+INSERT INTO translations_with_id ("local", "foreign", "guesses_local_total", "guesses_local_correct",
+                                  "guesses_foreign_total", "guesses_foreign_correct")
+SELECT "local", "foreign", "guesses_local_total", "guesses_local_correct",
+       "guesses_foreign_total", "guesses_foreign_correct"
+FROM translations;
+
+DROP TABLE translations;
+
+ALTER TABLE translations_with_id RENAME TO translations;
+
+CREATE TABLE reviews
+(
+    "id"               INTEGER   NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "translation_id"   INTEGER   NOT NULL REFERENCES translations ("id") ON DELETE CASCADE,
+    "reviewed_at"      TIMESTAMP NOT NULL,
+    "direction"        VARCHAR   NOT NULL,
+    "answer"           VARCHAR   NOT NULL,
+    "correct"          BOOLEAN   NOT NULL,
+    "response_time_ms" INTEGER   NOT NULL
+);
+
+CREATE INDEX reviews_translation ON reviews ("translation_id", "reviewed_at");
@@ -0,0 +1,7 @@
+ALTER TABLE translations ADD COLUMN "ease_factor" REAL NOT NULL DEFAULT 2.5;
+ALTER TABLE translations ADD COLUMN "interval_days" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE translations ADD COLUMN "repetitions" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE translations ADD COLUMN "lapses" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE translations ADD COLUMN "due_at" TIMESTAMP NULL;
+
+CREATE INDEX translations_due_at ON translations ("due_at");
@@ -0,0 +1,14 @@
+CREATE TABLE tags
+(
+    "id"   INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "name" VARCHAR NOT NULL UNIQUE
+);
+
+CREATE TABLE translation_tags
+(
+    "translation_id" INTEGER NOT NULL REFERENCES translations ("id") ON DELETE CASCADE,
+    "tag_id"         INTEGER NOT NULL REFERENCES tags ("id") ON DELETE CASCADE,
+    PRIMARY KEY ("translation_id", "tag_id")
+);
+
+CREATE INDEX translation_tags_tag ON translation_tags ("tag_id");
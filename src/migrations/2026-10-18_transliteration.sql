@@ -0,0 +1 @@
+ALTER TABLE decks ADD COLUMN "transliteration" VARCHAR NULL;
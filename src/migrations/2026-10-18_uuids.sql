@@ -0,0 +1,9 @@
+ALTER TABLE translations ADD COLUMN "uuid" VARCHAR NOT NULL DEFAULT '';
+
+-- Random (version 4) UUIDs for the existing translations
+UPDATE translations
+SET "uuid" = lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || substr(hex(randomblob(2)), 2) || '-' ||
+                   substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' ||
+                   hex(randomblob(6)));
+
+CREATE UNIQUE INDEX translations_uuid ON translations ("uuid");
@@ -1,43 +1,161 @@
+use std::fmt::Display;
 use std::io;
+use std::str::FromStr;
 
+use chrono::NaiveDateTime;
 use csv::{Reader, Writer};
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::porter::ExporterError;
+use crate::vocab_store::new_uuid;
+use crate::{Alternates, Gender, PartOfSpeech};
 
 #[derive(Deserialize, Serialize)]
 struct Translation {
+    // Exports from before uuids were added won't have one, they're given a new one
+    #[serde(default)]
+    pub uuid: String,
     pub local: String,
     pub foreign: String,
     pub guesses_local_total: i32,
     pub guesses_local_correct: i32,
     pub guesses_foreign_total: i32,
     pub guesses_foreign_correct: i32,
+    // Exports from before scheduling was added won't have these columns
+    #[serde(default = "default_ease_factor")]
+    pub ease_factor: f64,
+    #[serde(default)]
+    pub interval_days: i32,
+    #[serde(default)]
+    pub repetitions: i32,
+    #[serde(default)]
+    pub lapses: i32,
+    #[serde(default)]
+    pub due_at: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub last_reviewed_at: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub local_alternates: String,
+    #[serde(default)]
+    pub foreign_alternates: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub examples: String,
+    #[serde(default)]
+    pub mnemonic: Option<String>,
+    #[serde(default)]
+    pub reading: Option<String>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display")]
+    pub part_of_speech: Option<PartOfSpeech>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display")]
+    pub gender: Option<Gender>,
+    #[serde(default)]
+    pub plural: Option<String>,
+    #[serde(default)]
+    pub tags: String,
+    #[serde(default)]
+    pub guesses_local_hinted: i32,
+    #[serde(default)]
+    pub guesses_foreign_hinted: i32,
 }
 
-impl From<crate::Translation> for Translation {
-    fn from(t: crate::Translation) -> Self {
+fn default_ease_factor() -> f64 {
+    crate::Translation::default().ease_factor
+}
+
+/// Reads a column that is written the way its type is parsed, so a value that can't be parsed is
+/// an error naming the row rather than being lost
+fn parse<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    Option::<String>::deserialize(deserializer)?
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| value.parse().map_err(de::Error::custom))
+        .transpose()
+}
+
+fn display<S, T>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Display,
+{
+    value
+        .as_ref()
+        .map(ToString::to_string)
+        .serialize(serializer)
+}
+
+impl Translation {
+    fn new(t: crate::Translation, tags: &[String]) -> Self {
         Translation {
+            uuid: t.uuid,
             local: t.local,
             foreign: t.foreign,
             guesses_local_total: t.guesses_local_total,
             guesses_local_correct: t.guesses_local_correct,
             guesses_foreign_total: t.guesses_foreign_total,
             guesses_foreign_correct: t.guesses_foreign_correct,
+            ease_factor: t.ease_factor,
+            interval_days: t.interval_days,
+            repetitions: t.repetitions,
+            lapses: t.lapses,
+            due_at: t.due_at,
+            last_reviewed_at: t.last_reviewed_at,
+            local_alternates: t.local_alternates.to_string(),
+            foreign_alternates: t.foreign_alternates.to_string(),
+            notes: t.notes,
+            examples: t.examples.to_string(),
+            mnemonic: t.mnemonic,
+            reading: t.reading,
+            part_of_speech: t.part_of_speech,
+            gender: t.gender,
+            plural: t.plural,
+            // Tags are separated like alternates, and can't contain the separator either
+            tags: Alternates::new(tags).to_string(),
+            guesses_local_hinted: t.guesses_local_hinted,
+            guesses_foreign_hinted: t.guesses_foreign_hinted,
         }
     }
-}
 
-impl From<Translation> for crate::Translation {
-    fn from(t: Translation) -> Self {
-        crate::Translation {
-            local: t.local,
-            foreign: t.foreign,
-            guesses_local_total: t.guesses_local_total,
-            guesses_local_correct: t.guesses_local_correct,
-            guesses_foreign_total: t.guesses_foreign_total,
-            guesses_foreign_correct: t.guesses_foreign_correct,
-        }
+    fn into_parts(self) -> (crate::Translation, Vec<String>) {
+        let tags = self.tags.parse::<Alternates>().unwrap_or_default().to_vec();
+        let translation = crate::Translation {
+            id: 0,
+            deck_id: 0,
+            uuid: if self.uuid.is_empty() {
+                new_uuid()
+            } else {
+                self.uuid
+            },
+            local: self.local,
+            foreign: self.foreign,
+            guesses_local_total: self.guesses_local_total,
+            guesses_local_correct: self.guesses_local_correct,
+            guesses_foreign_total: self.guesses_foreign_total,
+            guesses_foreign_correct: self.guesses_foreign_correct,
+            ease_factor: self.ease_factor,
+            interval_days: self.interval_days,
+            repetitions: self.repetitions,
+            lapses: self.lapses,
+            due_at: self.due_at,
+            last_reviewed_at: self.last_reviewed_at,
+            local_alternates: self.local_alternates.parse().unwrap_or_default(),
+            foreign_alternates: self.foreign_alternates.parse().unwrap_or_default(),
+            notes: self.notes,
+            examples: self.examples.parse().unwrap_or_default(),
+            mnemonic: self.mnemonic,
+            reading: self.reading,
+            part_of_speech: self.part_of_speech,
+            gender: self.gender,
+            plural: self.plural,
+            guesses_local_hinted: self.guesses_local_hinted,
+            guesses_foreign_hinted: self.guesses_foreign_hinted,
+        };
+        (translation, tags)
     }
 }
 
@@ -54,13 +172,14 @@ impl<R: io::Read> CsvReader<R> {
 }
 
 impl<R: io::Read> Iterator for CsvReader<R> {
-    type Item = Result<crate::Translation, ExporterError>;
+    /// Each translation along with its tags
+    type Item = Result<(crate::Translation, Vec<String>), ExporterError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.reader
             .deserialize::<Translation>()
             .next()
-            .map(|res| res.map(|rec| rec.into()).map_err(|e| e.into()))
+            .map(|res| res.map(Translation::into_parts).map_err(|e| e.into()))
     }
 }
 
@@ -75,10 +194,75 @@ impl<W: io::Write> CsvWriter<W> {
         }
     }
 
-    pub fn write(&mut self, translation: crate::Translation) -> Result<(), ExporterError> {
-        let csv_translation: Translation = translation.into();
+    pub fn write(
+        &mut self,
+        translation: crate::Translation,
+        tags: &[String],
+    ) -> Result<(), ExporterError> {
+        let csv_translation = Translation::new(translation, tags);
         self.writer.serialize(csv_translation)?;
         self.writer.flush()?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CsvReader, CsvWriter};
+    use crate::porter::ExporterError;
+    use crate::{Gender, PartOfSpeech, Translation};
+
+    #[test]
+    fn test_round_trip() {
+        let mut translation = Translation::new("yes", "はい");
+        translation.notes = Some("Also used to show you are listening".to_string());
+        translation.examples.add("はい、そうです。");
+        translation.examples.add("はい、分かりました。");
+        translation.mnemonic = Some("Hi! Yes?".to_string());
+        translation.local_alternates.add("yeah");
+        translation.reading = Some("hai".to_string());
+        translation.part_of_speech = Some(PartOfSpeech::Interjection);
+        translation.guesses_foreign_total = 2;
+        translation.guesses_foreign_hinted = 1;
+        let tags = vec!["greetings".to_string(), "jlpt n5".to_string()];
+
+        let mut csv = Vec::new();
+        CsvWriter::new(&mut csv)
+            .write(translation.clone(), &tags)
+            .unwrap();
+        let read: Vec<_> = CsvReader::new(csv.as_slice()).map(|t| t.unwrap()).collect();
+
+        assert_eq!(read, vec![(translation, tags)]);
+    }
+
+    #[test]
+    fn test_reads_old_exports() {
+        let csv = "local,foreign,guesses_local_total,guesses_local_correct,guesses_foreign_total,guesses_foreign_correct\nyes,はい,1,1,0,0\n";
+        let read: Vec<_> = CsvReader::new(csv.as_bytes()).map(|t| t.unwrap()).collect();
+
+        assert_eq!(read.len(), 1);
+        let (translation, tags) = &read[0];
+        assert_eq!(translation.guesses_local_correct, 1);
+        assert_eq!(translation.notes, None);
+        assert!(translation.examples.is_empty());
+        assert!(!translation.uuid.is_empty());
+        assert!(tags.is_empty());
+    }
+
+    #[test]
+    fn test_rejects_unknown_grammar() {
+        let csv = "local,foreign,guesses_local_total,guesses_local_correct,guesses_foreign_total,guesses_foreign_correct,part_of_speech,gender\nhouse,Haus,0,0,0,0,noun,n\ndog,Hund,0,0,0,0,nuon,m\n";
+        let read: Vec<_> = CsvReader::new(csv.as_bytes()).collect();
+
+        let (house, _) = read[0].as_ref().unwrap();
+        assert_eq!(house.part_of_speech, Some(PartOfSpeech::Noun));
+        assert_eq!(house.gender, Some(Gender::Neuter));
+        match &read[1] {
+            Err(ExporterError::CsvError(e)) => {
+                assert_eq!(e.position().map(|p| p.line()), Some(3));
+                assert!(e.to_string().contains("nuon"));
+            }
+            _ => panic!("CsvReader did not return CsvError"),
+        }
+    }
+}
@@ -1,4 +1,3 @@
-use csv;
 pub use csv_io::{CsvReader, CsvWriter};
 use std::io;
 
@@ -1,12 +1,87 @@
 table! {
-    translations (local, foreign) {
+    translations (id) {
+        id -> Integer,
+        deck_id -> Integer,
+        uuid -> Text,
         local -> Text,
         foreign -> Text,
         guesses_local_total -> Integer,
         guesses_local_correct -> Integer,
         guesses_foreign_total -> Integer,
         guesses_foreign_correct -> Integer,
+        ease_factor -> Double,
+        interval_days -> Integer,
+        repetitions -> Integer,
+        lapses -> Integer,
+        due_at -> Nullable<Timestamp>,
+        last_reviewed_at -> Nullable<Timestamp>,
+        local_alternates -> Text,
+        foreign_alternates -> Text,
+        notes -> Nullable<Text>,
+        examples -> Text,
+        mnemonic -> Nullable<Text>,
+        reading -> Nullable<Text>,
+        part_of_speech -> Nullable<Text>,
+        gender -> Nullable<Text>,
+        plural -> Nullable<Text>,
+        guesses_local_hinted -> Integer,
+        guesses_foreign_hinted -> Integer,
     }
 }
 
-no_arg_sql_function!(RANDOM, (), "Represents the sql RANDOM() function");
+table! {
+    reviews (id) {
+        id -> Integer,
+        translation_id -> Integer,
+        reviewed_at -> Timestamp,
+        direction -> Text,
+        answer -> Text,
+        correct -> Bool,
+        response_time_ms -> Integer,
+        outcome -> Text,
+        grade -> Text,
+    }
+}
+
+table! {
+    tags (id) {
+        id -> Integer,
+        name -> Text,
+    }
+}
+
+table! {
+    translation_tags (translation_id, tag_id) {
+        translation_id -> Integer,
+        tag_id -> Integer,
+    }
+}
+
+table! {
+    decks (id) {
+        id -> Integer,
+        name -> Text,
+        local_language -> Text,
+        foreign_language -> Text,
+        show_reading -> Text,
+        near_miss -> Text,
+        ignore_diacritics -> Bool,
+        case_sensitive -> Bool,
+        locale -> Text,
+        transliteration -> Nullable<Text>,
+        overrule_wrong -> Bool,
+    }
+}
+
+joinable!(reviews -> translations (translation_id));
+joinable!(translations -> decks (deck_id));
+joinable!(translation_tags -> translations (translation_id));
+joinable!(translation_tags -> tags (tag_id));
+allow_tables_to_appear_in_same_query!(decks, reviews, tags, translation_tags, translations);
+
+table! {
+    metadata (key) {
+        key -> Text,
+        value -> Text,
+    }
+}
@@ -3,24 +3,60 @@ use std::error::Error;
 use std::io;
 use std::path::Path;
 
+use chrono::{NaiveDateTime, Utc};
 use diesel::result::DatabaseErrorKind;
+use diesel::sqlite::Sqlite;
 use diesel::{
-    result::Error as DieselError, Connection, ConnectionError, ExpressionMethods, QueryDsl,
-    RunQueryDsl, SqliteConnection,
+    result::Error as DieselError, Connection, ConnectionError, ExpressionMethods,
+    OptionalExtension, QueryDsl, RunQueryDsl, SqliteConnection,
 };
 
-pub use crate::porter::{CsvWriter, ExporterError};
+pub use crate::porter::ExporterError;
 use crate::vocab_store::entires::Entries;
-pub use guess::Guess;
+use crate::vocab_store::review::NewReview;
+use crate::vocab_store::translation::NewTranslation;
+pub use alternates::Alternates;
+use answer::same_word;
+pub use answer::{check_answer, edit_distance, fold_case, Match, Matcher, NearMiss, Outcome};
+pub use decks::{Deck, ShowReading};
+pub use diff::{closest_answer, diff, Difference};
+pub use examples::Examples;
+pub use grammar::{Gender, PartOfSpeech};
+pub use guess::{Attempt, Direction, Guess};
 use guesses::Guesses;
+pub use location::{find_database, global_database, DATABASE_FILE};
+pub use migration::MigrationStatus;
+pub use review::Review;
+pub use scheduler::Grade;
+pub use strategy::{
+    DueForReview, LeastRecentlyReviewed, NewWordsFirst, SelectionStrategy, UniformRandom,
+    WeakestFirst, WeightedRandom,
+};
+pub use syntax::{choices, parse_answers, variants, Choice, MAX_OPTIONAL_PARTS};
+pub use tags::Tag;
+pub(crate) use translation::new_uuid;
 pub use translation::Translation;
+pub use transliteration::{Cyrillic, Greek, Hangul, Romaji, Transliterate, Transliteration};
 
+mod alternates;
+mod answer;
+mod decks;
+mod diff;
 mod entires;
+mod examples;
+mod grammar;
 mod guess;
 mod guesses;
+mod list;
+mod location;
+mod migration;
+mod review;
+mod scheduler;
+mod strategy;
+mod syntax;
+mod tags;
 mod translation;
-
-const INIT: &str = include_str!("migrations/2020-02-22_vocab_table.sql");
+mod transliteration;
 
 #[derive(Debug)]
 pub enum VocabStoreError {
@@ -32,6 +68,18 @@ pub enum VocabStoreError {
     UnexpectedError(Box<dyn Error>),
     ExporterError(ExporterError),
     ReconciliationError,
+    SchemaTooNew {
+        found: i32,
+        supported: i32,
+    },
+    InvalidTag,
+    DeckNotFound,
+    DeckAlreadyExists,
+    DeckInUse,
+    /// A translation that isn't in the current deck, eg one that has been removed
+    TranslationNotFound,
+    /// A word whose brackets don't match, or with too many optional parts
+    InvalidAnswer(String),
 }
 
 impl fmt::Display for VocabStoreError {
@@ -76,7 +124,11 @@ impl From<ExporterError> for VocabStoreError {
 
 type VSResult<T> = Result<T, VocabStoreError>;
 
-pub struct VocabStore(SqliteConnection);
+pub struct VocabStore {
+    conn: SqliteConnection,
+    /// The deck that translations are added to and read from
+    deck_id: i32,
+}
 
 impl VocabStore {
     pub fn from(file: &str) -> VSResult<VocabStore> {
@@ -84,7 +136,18 @@ impl VocabStore {
             return Err(VocabStoreError::NotInitialised);
         }
         let connection = SqliteConnection::establish(file)?;
-        Ok(VocabStore(connection))
+        let status = migration::status(&connection)?;
+        if status.is_too_new() {
+            return Err(VocabStoreError::SchemaTooNew {
+                found: status.current,
+                supported: status.latest,
+            });
+        }
+        if status.needs_backup() {
+            migration::backup(file, status.current)?;
+        }
+        migration::migrate(&connection)?;
+        VocabStore::open(connection)
     }
 
     pub fn init(file: &str) -> VSResult<VocabStore> {
@@ -92,40 +155,290 @@ impl VocabStore {
             return Err(VocabStoreError::AlreadyInitialised);
         }
         let connection = SqliteConnection::establish(file)?;
-        diesel::sql_query(INIT).execute(&connection)?;
-        Ok(VocabStore(connection))
+        migration::migrate(&connection)?;
+        VocabStore::open(connection)
+    }
+
+    fn open(conn: SqliteConnection) -> VSResult<VocabStore> {
+        let deck_id = decks::current_deck_id(&conn)?;
+        Ok(VocabStore { conn, deck_id })
+    }
+
+    /// Runs `f` in a single transaction, so the changes it makes through the store are either all
+    /// kept or, if it returns an error, all undone
+    pub fn transaction<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+        E: From<DieselError>,
+    {
+        self.conn.transaction(f)
+    }
+
+    /// Reports the schema version of a database without upgrading it
+    pub fn migration_status(file: &str) -> VSResult<MigrationStatus> {
+        if !Path::new(file).exists() {
+            return Err(VocabStoreError::NotInitialised);
+        }
+        let connection = SqliteConnection::establish(file)?;
+        migration::status(&connection)
     }
 
+    /// Adds a translation to the current deck. If its uuid is already in use, eg because it was
+    /// exported from another deck, it is given a new one.
     pub fn add(&self, translation: &Translation) -> VSResult<()> {
+        use crate::schema::translations::dsl::*;
+
+        translation.check_syntax()?;
+        // The database only stops exact duplicates, not ones that differ in case
+        if self
+            .find(&translation.local, &translation.foreign)?
+            .is_some()
+        {
+            return Err(VocabStoreError::DuplicateEntry);
+        }
+
+        let uuid_in_use = translations
+            .filter(uuid.eq(&translation.uuid))
+            .count()
+            .get_result::<i64>(&self.conn)?
+            > 0;
+        let fresh_uuid = new_uuid();
+        let new_translation = NewTranslation {
+            deck_id: self.deck_id,
+            uuid: if uuid_in_use {
+                &fresh_uuid
+            } else {
+                &translation.uuid
+            },
+            ..NewTranslation::from(translation)
+        };
         diesel::insert_into(crate::schema::translations::table)
-            .values(translation)
-            .execute(&self.0)?;
+            .values(new_translation)
+            .execute(&self.conn)?;
         Ok(())
     }
 
+    /// Updates a stored translation. Translations that did not come from the store (and so have
+    /// no id) are matched on their local and foreign words in the current deck instead.
     pub fn save(&self, translation: &Translation) -> VSResult<()> {
-        diesel::replace_into(crate::schema::translations::table)
-            .values(translation)
-            .execute(&self.0)?;
+        use crate::schema::translations::dsl::*;
+
+        if translation.id != 0 {
+            diesel::update(translation)
+                .set(translation)
+                .execute(&self.conn)?;
+        } else {
+            let translation = Translation {
+                deck_id: self.deck_id,
+                ..translation.clone()
+            };
+            diesel::update(
+                translations
+                    .filter(deck_id.eq(self.deck_id))
+                    .filter(local.eq(&translation.local))
+                    .filter(foreign.eq(&translation.foreign)),
+            )
+            .set(&translation)
+            .execute(&self.conn)?;
+        }
         Ok(())
     }
 
-    pub fn find_local(&self, find_local: &str) -> VSResult<Option<Translation>> {
+    /// Saves the guess's translation along with a review for every answer given to it since it
+    /// was last saved, so that the counters and the review log always agree.
+    pub fn save_guess(&self, guess: &mut Guess) -> VSResult<()> {
+        let attempts = guess.take_attempts();
+        self.conn.transaction::<_, VocabStoreError, _>(|| {
+            self.save(guess)?;
+            for attempt in &attempts {
+                diesel::insert_into(crate::schema::reviews::table)
+                    .values(NewReview::from_attempt(guess.id, attempt))
+                    .execute(&self.conn)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Every review of a translation, oldest first
+    pub fn reviews(&self, translation: &Translation) -> VSResult<Vec<Review>> {
+        use crate::schema::reviews::dsl::*;
+
+        Ok(reviews
+            .filter(translation_id.eq(translation.id))
+            .order((reviewed_at.asc(), id.asc()))
+            .load::<Review>(&self.conn)?)
+    }
+
+    /// The most recent review of a translation, if it has ever been reviewed
+    pub fn last_review(&self, translation: &Translation) -> VSResult<Option<Review>> {
+        use crate::schema::reviews::dsl::*;
+
+        Ok(reviews
+            .filter(translation_id.eq(translation.id))
+            .order((reviewed_at.desc(), id.desc()))
+            .first::<Review>(&self.conn)
+            .optional()?)
+    }
+
+    /// Deletes a translation along with its reviews and tags
+    pub fn remove(&self, translation: &Translation) -> VSResult<()> {
+        use crate::schema::translations::dsl::*;
+
+        self.conn.transaction(|| {
+            if translation.id != 0 {
+                diesel::delete(translation).execute(&self.conn)?;
+            } else {
+                diesel::delete(
+                    translations
+                        .filter(deck_id.eq(self.deck_id))
+                        .filter(local.eq(&translation.local))
+                        .filter(foreign.eq(&translation.foreign)),
+                )
+                .execute(&self.conn)?;
+            }
+            tags::delete_unused_tags(&self.conn)
+        })
+    }
+
+    /// Changes the words of a stored translation, keeping its statistics, schedule and reviews.
+    /// Translations that did not come from the store (and so have no id) are matched on their
+    /// uuid in the current deck instead. Fails with `DuplicateEntry` if the current deck already
+    /// has a translation between the new words, or `TranslationNotFound` if nothing was renamed.
+    pub fn rename(
+        &self,
+        translation: &Translation,
+        new_local: &str,
+        new_foreign: &str,
+    ) -> VSResult<Translation> {
+        use crate::schema::translations::dsl::*;
+
+        let renamed = Translation {
+            local: new_local.to_string(),
+            foreign: new_foreign.to_string(),
+            ..translation.clone()
+        };
+        parse_answers(new_local)?;
+        parse_answers(new_foreign)?;
+        if let Some(existing) = self.find(&renamed.local, &renamed.foreign)? {
+            if existing.uuid != translation.uuid {
+                return Err(VocabStoreError::DuplicateEntry);
+            }
+        }
+        let words = (local.eq(&renamed.local), foreign.eq(&renamed.foreign));
+        let updated = if translation.id != 0 {
+            diesel::update(translations.find(translation.id))
+                .set(words)
+                .execute(&self.conn)?
+        } else {
+            diesel::update(
+                translations
+                    .filter(deck_id.eq(self.deck_id))
+                    .filter(uuid.eq(&translation.uuid)),
+            )
+            .set(words)
+            .execute(&self.conn)?
+        };
+        if updated == 0 {
+            return Err(VocabStoreError::TranslationNotFound);
+        }
+        Ok(renamed)
+    }
+
+    /// Every translation in the current deck matching a filter. Words are compared in Rust as
+    /// SQLite only knows how to ignore the case of ASCII.
+    fn find_where<F: Fn(&Translation) -> bool>(&self, filter: F) -> VSResult<Vec<Translation>> {
+        Ok(deck_translations(self.deck_id, None)
+            .order(crate::schema::translations::id)
+            .load::<Translation>(&self.conn)?
+            .into_iter()
+            .filter(filter)
+            .collect())
+    }
+
+    /// Every translation of a local word in the current deck, in any case
+    pub fn find_local(&self, find_local: &str) -> VSResult<Vec<Translation>> {
+        self.find_where(|t| same_word(&t.local, find_local))
+    }
+
+    /// Every translation of a foreign word in the current deck, in any case
+    pub fn find_foreign(&self, find_foreign: &str) -> VSResult<Vec<Translation>> {
+        self.find_where(|t| same_word(&t.foreign, find_foreign))
+    }
+
+    /// The translation in the current deck with the given uuid
+    pub fn find_by_uuid(&self, find_uuid: &str) -> VSResult<Option<Translation>> {
         use crate::schema::translations::dsl::*;
 
         Ok(translations
-            .filter(local.eq(find_local))
-            .limit(1)
-            .load::<Translation>(&self.0)?
-            .pop())
+            .filter(deck_id.eq(self.deck_id))
+            .filter(uuid.eq(find_uuid))
+            .first::<Translation>(&self.conn)
+            .optional()?)
     }
 
-    pub fn guesses(&self) -> Guesses {
-        Guesses::new(&self.0)
+    /// The translation between this local and foreign word in the current deck, in any case
+    pub fn find(&self, find_local: &str, find_foreign: &str) -> VSResult<Option<Translation>> {
+        Ok(self
+            .find_where(|t| same_word(&t.local, find_local) && same_word(&t.foreign, find_foreign))?
+            .into_iter()
+            .next())
     }
 
-    pub fn entries(&self) -> Entries {
-        Entries::new(&self.0)
+    /// Guesses from the current deck chosen by the given strategy, until the strategy stops
+    /// selecting translations
+    pub fn guesses<'a, S: SelectionStrategy + 'a>(&'a self, strategy: S) -> Guesses<'a> {
+        Guesses::new(&self.conn, self.deck_id, Box::new(strategy))
+    }
+
+    /// When the next translation in the current deck will become due, `None` if there are no
+    /// translations
+    pub fn next_due(&self) -> VSResult<Option<NaiveDateTime>> {
+        use crate::schema::translations::dsl::*;
+
+        let never_reviewed = translations
+            .filter(deck_id.eq(self.deck_id))
+            .filter(due_at.is_null())
+            .count()
+            .get_result::<i64>(&self.conn)?;
+        if never_reviewed > 0 {
+            return Ok(Some(Utc::now().naive_utc()));
+        }
+        Ok(translations
+            .select(due_at)
+            .filter(deck_id.eq(self.deck_id))
+            .filter(due_at.is_not_null())
+            .order(due_at.asc())
+            .first::<Option<NaiveDateTime>>(&self.conn)
+            .optional()?
+            .flatten())
+    }
+
+    /// Every translation in the current deck
+    pub fn entries(&self) -> Entries<'_> {
+        Entries::new(&self.conn, self.deck_id)
+    }
+}
+
+/// The translations in a deck, or only those with the given tag
+pub(crate) fn deck_translations(
+    deck: i32,
+    tag: Option<&str>,
+) -> crate::schema::translations::BoxedQuery<'static, Sqlite> {
+    use crate::schema::{tags, translation_tags, translations};
+
+    let query = translations::table
+        .filter(translations::deck_id.eq(deck))
+        .into_boxed();
+    match tag {
+        None => query,
+        Some(tag) => query.filter(
+            translations::id.eq_any(
+                translation_tags::table
+                    .inner_join(tags::table)
+                    .filter(tags::name.eq(tag.trim().to_lowercase()))
+                    .select(translation_tags::translation_id),
+            ),
+        ),
     }
 }
 
@@ -135,39 +448,39 @@ mod test {
 
     use diesel::{Connection, RunQueryDsl, SqliteConnection};
 
-    use crate::{Translation, VocabStore, VocabStoreError};
+    use crate::{Direction, Grade, Translation, UniformRandom, VocabStore, VocabStoreError};
 
     const TEST_FILE: &str = "test.sqlite";
 
     #[test]
     fn test_from() {
-        let _ = fs::remove_file(&TEST_FILE); // Ok if it fails;
-        SqliteConnection::establish(&TEST_FILE).unwrap();
-        assert!(VocabStore::from(&TEST_FILE).is_ok());
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        SqliteConnection::establish(TEST_FILE).unwrap();
+        assert!(VocabStore::from(TEST_FILE).is_ok());
     }
 
     #[test]
     fn test_from_error() {
-        let _ = fs::remove_file(&TEST_FILE); // Ok if it fails;
-        match VocabStore::from(&TEST_FILE) {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        match VocabStore::from(TEST_FILE) {
             Err(VocabStoreError::NotInitialised) => {}
-            _ => assert!(false, "VocabStore did not return NotInitialised error"),
+            _ => panic!("VocabStore did not return NotInitialised error"),
         }
     }
 
     #[test]
     fn test_init() {
-        let _ = fs::remove_file(&TEST_FILE); // Ok if it fails;
-        assert!(VocabStore::init(&TEST_FILE).is_ok());
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        assert!(VocabStore::init(TEST_FILE).is_ok());
     }
 
     #[test]
     fn test_init_error() {
-        let _ = fs::remove_file(&TEST_FILE); // Ok if it fails;
-        SqliteConnection::establish(&TEST_FILE).unwrap();
-        match VocabStore::init(&TEST_FILE) {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        SqliteConnection::establish(TEST_FILE).unwrap();
+        match VocabStore::init(TEST_FILE) {
             Err(VocabStoreError::AlreadyInitialised) => {}
-            _ => assert!(false, "VocabStore did not return AlreadyInitialised error"),
+            _ => panic!("VocabStore did not return AlreadyInitialised error"),
         }
     }
 
@@ -175,12 +488,12 @@ mod test {
     fn test_add() {
         use crate::schema::translations::dsl::*;
 
-        let _ = fs::remove_file(&TEST_FILE); // Ok if it fails;
-        let vocab_store = VocabStore::init(&TEST_FILE).unwrap();
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
         let translation = Translation::new("yes", "はい");
         vocab_store.add(&translation).unwrap();
 
-        let conn = SqliteConnection::establish(&TEST_FILE).unwrap();
+        let conn = SqliteConnection::establish(TEST_FILE).unwrap();
         let t: Translation = translations.load(&conn).unwrap().pop().unwrap();
 
         assert_eq!(t.local, "yes");
@@ -188,51 +501,59 @@ mod test {
     }
 
     #[test]
-    fn test_add_duplicate_local() {
-        let _ = fs::remove_file(&TEST_FILE); // Ok if it fails;
-        let vocab_store = VocabStore::init(&TEST_FILE).unwrap();
+    fn test_add_duplicate() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
         let translation = Translation::new("yes", "はい");
         vocab_store.add(&translation).unwrap();
-        let different_foreign = Translation::new("no", "はい");
-        match vocab_store.add(&different_foreign) {
+        let same_translation = Translation::new("yes", "はい");
+        match vocab_store.add(&same_translation) {
             Err(VocabStoreError::DuplicateEntry) => {}
-            Err(e) => assert!(
-                false,
-                "VocabStore did not return DuplicateEntry error: {:?}",
-                e
-            ),
-            Ok(_) => assert!(false, "VocabStore did not return DuplicateEntry error"),
+            Err(e) => panic!("VocabStore did not return DuplicateEntry error: {:?}", e),
+            Ok(_) => panic!("VocabStore did not return DuplicateEntry error"),
         }
     }
 
     #[test]
-    fn test_add_duplicate_foreign() {
-        let _ = fs::remove_file(&TEST_FILE); // Ok if it fails;
-        let vocab_store = VocabStore::init(&TEST_FILE).unwrap();
-        let translation = Translation::new("yes", "はい");
-        vocab_store.add(&translation).unwrap();
-        let different_local = Translation::new("no", "はい");
-        match vocab_store.add(&different_local) {
-            Err(VocabStoreError::DuplicateEntry) => {}
-            Err(e) => assert!(
-                false,
-                "VocabStore did not return DuplicateEntry error: {:?}",
-                e
-            ),
-            Ok(_) => assert!(false, "VocabStore did not return DuplicateEntry error"),
-        }
+    fn test_add_synonym_local() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        vocab_store
+            .add(&Translation::new("hello", "こんにちは"))
+            .unwrap();
+        vocab_store
+            .add(&Translation::new("hello", "もしもし"))
+            .unwrap();
+
+        let found = vocab_store.find_local("hello").unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].foreign, "こんにちは");
+        assert_eq!(found[1].foreign, "もしもし");
+    }
+
+    #[test]
+    fn test_add_synonym_foreign() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        vocab_store.add(&Translation::new("yes", "はい")).unwrap();
+        vocab_store.add(&Translation::new("okay", "はい")).unwrap();
+
+        let found = vocab_store.find_foreign("はい").unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(vocab_store.find("okay", "はい").unwrap().is_some());
+        assert!(vocab_store.find("okay", "いいえ").unwrap().is_none());
     }
 
     #[test]
     fn test_save() {
         use crate::schema::translations::dsl::*;
 
-        let _ = fs::remove_file(&TEST_FILE); // Ok if it fails;
-        let vocab_store = VocabStore::init(&TEST_FILE).unwrap();
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
         let mut translation = Translation::new("yes", "はい");
         vocab_store.add(&translation).unwrap();
 
-        let conn = SqliteConnection::establish(&TEST_FILE).unwrap();
+        let conn = SqliteConnection::establish(TEST_FILE).unwrap();
         let t: Translation = translations.load(&conn).unwrap().pop().unwrap();
 
         assert_eq!(t.guesses_foreign_total, 0);
@@ -240,9 +561,170 @@ mod test {
         translation.guesses_foreign_total = 2;
         vocab_store.save(&translation).unwrap();
 
-        let conn = SqliteConnection::establish(&TEST_FILE).unwrap();
+        let conn = SqliteConnection::establish(TEST_FILE).unwrap();
         let t: Translation = translations.load(&conn).unwrap().pop().unwrap();
 
         assert_eq!(t.guesses_foreign_total, 2);
     }
+
+    #[test]
+    fn test_find_by_uuid() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let mut vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        let translation = Translation::new("yes", "はい");
+        vocab_store.add(&translation).unwrap();
+        let stored = vocab_store
+            .find_by_uuid(&translation.uuid)
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.local, "yes");
+
+        // Copying the translation to another deck gives it a new uuid
+        vocab_store.create_deck("copy", "", "").unwrap();
+        vocab_store.use_deck("copy").unwrap();
+        assert!(vocab_store
+            .find_by_uuid(&translation.uuid)
+            .unwrap()
+            .is_none());
+        vocab_store.add(&translation).unwrap();
+        let copy = vocab_store.find("yes", "はい").unwrap().unwrap();
+        assert_ne!(copy.uuid, translation.uuid);
+    }
+
+    #[test]
+    fn test_remove() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        vocab_store.add(&Translation::new("yes", "はい")).unwrap();
+        vocab_store.add(&Translation::new("no", "いいえ")).unwrap();
+        let mut guess = vocab_store.guesses(UniformRandom).next().unwrap().unwrap();
+        guess.guess("wrong");
+        vocab_store.save_guess(&mut guess).unwrap();
+
+        vocab_store.remove(&guess).unwrap();
+        assert!(vocab_store
+            .find(&guess.local, &guess.foreign)
+            .unwrap()
+            .is_none());
+        assert!(vocab_store.reviews(&guess).unwrap().is_empty());
+        assert_eq!(vocab_store.entries().count(), 1);
+    }
+
+    #[test]
+    fn test_rename() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        vocab_store.add(&Translation::new("yse", "はい")).unwrap();
+        vocab_store.add(&Translation::new("no", "いいえ")).unwrap();
+        let mut typo = vocab_store.find("yse", "はい").unwrap().unwrap();
+        typo.guesses_foreign_total = 3;
+        vocab_store.save(&typo).unwrap();
+
+        let renamed = vocab_store.rename(&typo, "Yes", "はい").unwrap();
+        assert_eq!(renamed.local, "Yes");
+        let stored = vocab_store.find("yes", "はい").unwrap().unwrap();
+        assert_eq!(stored, renamed);
+        assert_eq!(stored.id, typo.id);
+        assert_eq!(stored.guesses_foreign_total, 3);
+        assert!(vocab_store.find("yse", "はい").unwrap().is_none());
+
+        match vocab_store.rename(&stored, "no", "いいえ") {
+            Err(VocabStoreError::DuplicateEntry) => {}
+            _ => panic!("VocabStore did not return DuplicateEntry error"),
+        }
+    }
+
+    #[test]
+    fn test_rename_without_id() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        vocab_store.add(&Translation::new("yse", "はい")).unwrap();
+        let stored = vocab_store.find("yse", "はい").unwrap().unwrap();
+
+        let unsaved = Translation { id: 0, ..stored };
+        vocab_store.rename(&unsaved, "yes", "はい").unwrap();
+        assert!(vocab_store.find("yes", "はい").unwrap().is_some());
+
+        match vocab_store.rename(&Translation::new("no", "いいえ"), "nope", "いいえ") {
+            Err(VocabStoreError::TranslationNotFound) => {}
+            _ => panic!("VocabStore did not return TranslationNotFound error"),
+        }
+    }
+
+    #[test]
+    fn test_transaction_undoes_every_change_on_error() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        vocab_store.add(&Translation::new("yse", "はい")).unwrap();
+        vocab_store.add(&Translation::new("no", "いいえ")).unwrap();
+        let typo = vocab_store.find("yse", "はい").unwrap().unwrap();
+        let no = vocab_store.find("no", "いいえ").unwrap().unwrap();
+
+        let result = vocab_store.transaction(|| {
+            vocab_store.rename(&typo, "yes", "はい")?;
+            vocab_store.rename(&no, "yes", "はい")
+        });
+        assert!(result.is_err());
+        assert!(vocab_store.find("yse", "はい").unwrap().is_some());
+        assert!(vocab_store.find("yes", "はい").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_save_alternates() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        let mut translation = Translation::new("color", "couleur");
+        translation.alternates_mut(Direction::Local).add("colour");
+        vocab_store.add(&translation).unwrap();
+
+        let mut t = vocab_store.find("color", "couleur").unwrap().unwrap();
+        assert_eq!(t.answers(Direction::Local), vec!["color", "colour"]);
+        assert!(t.foreign_alternates.is_empty());
+
+        t.alternates_mut(Direction::Foreign).add("teinte");
+        t.alternates_mut(Direction::Local).remove("colour");
+        vocab_store.save(&t).unwrap();
+
+        let t = vocab_store.find("color", "couleur").unwrap().unwrap();
+        assert!(t.local_alternates.is_empty());
+        assert_eq!(t.answers(Direction::Foreign), vec!["couleur", "teinte"]);
+    }
+
+    #[test]
+    fn test_save_guess_records_reviews() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        vocab_store.add(&Translation::new("yes", "はい")).unwrap();
+
+        let mut guess = vocab_store.guesses(UniformRandom).next().unwrap().unwrap();
+        guess.guess("wrong");
+        let answer = guess.render_translation().to_string();
+        guess.guess(&answer);
+        guess.grade(Grade::Easy);
+        vocab_store.save_guess(&mut guess).unwrap();
+        assert!(guess.attempts().is_empty());
+
+        let translation = vocab_store.find("yes", "はい").unwrap().unwrap();
+        let reviews = vocab_store.reviews(&translation).unwrap();
+        assert_eq!(reviews.len(), 3);
+        assert_eq!(reviews[0].answer, "wrong");
+        assert!(!reviews[0].correct);
+        assert_eq!(reviews[0].grade, Grade::Again);
+        assert!(reviews[1].correct);
+        assert_eq!(reviews[1].direction, guess.direction());
+        assert_eq!(reviews[1].grade, Grade::Good);
+        assert_eq!(reviews[2].grade, Grade::Easy);
+        assert_eq!(
+            vocab_store.last_review(&translation).unwrap().as_ref(),
+            reviews.last()
+        );
+
+        let total = translation.guesses_local_total + translation.guesses_foreign_total;
+        let correct = translation.guesses_local_correct + translation.guesses_foreign_correct;
+        assert_eq!(total as usize, reviews.len());
+        assert_eq!(
+            correct as usize,
+            reviews.iter().filter(|r| r.correct).count()
+        );
+    }
 }
@@ -0,0 +1,53 @@
+use crate::vocab_store::answer::same_word;
+use crate::vocab_store::list::{List, ListKind};
+
+/// Other answers that are accepted for one side of a translation, eg "colour" for "color".
+///
+/// Stored (and exported) as a single `|` separated string.
+pub type Alternates = List<Alternate>;
+
+pub struct Alternate;
+
+impl ListKind for Alternate {
+    const SEPARATOR: char = '|';
+    const REPLACEMENT: &'static str = "";
+
+    /// Alternates are the same in any case
+    fn same(a: &str, b: &str) -> bool {
+        same_word(a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Alternates;
+
+    #[test]
+    fn test_add_and_remove() {
+        let mut alternates = Alternates::default();
+        assert!(alternates.add("Colour"));
+        assert!(alternates.add(" tv "));
+        assert!(!alternates.add("colour"));
+        assert!(!alternates.add(""));
+        assert_eq!(*alternates, vec!["Colour", "tv"]);
+
+        assert!(alternates.remove("TV"));
+        assert!(!alternates.remove("tv"));
+        assert_eq!(*alternates, vec!["Colour"]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let alternates = Alternates::new(&["colour", "tv"]);
+        assert_eq!(alternates.to_string(), "colour|tv");
+        assert_eq!("colour|tv".parse::<Alternates>().unwrap(), alternates);
+        assert!("".parse::<Alternates>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_union() {
+        let a = Alternates::new(&["colour", "tv"]);
+        let b = Alternates::new(&["tv", "telly"]);
+        assert_eq!(*a.union(&b), vec!["colour", "tv", "telly"]);
+    }
+}
@@ -0,0 +1,501 @@
+//! Checking typed answers against the answers a guess accepts. Both sides are normalised and case
+//! folded first so answers typed on different keyboards still match. Answers that are only a typo
+//! or two away from an accepted answer are near misses, which each deck can choose how to grade.
+
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use diesel::sqlite::Sqlite;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::vocab_store::scheduler::Grade;
+use crate::vocab_store::syntax::variants;
+use crate::vocab_store::transliteration::{Transliterate, Transliteration};
+
+/// How an answer compares to the accepted answers
+#[derive(Debug, Clone, PartialEq)]
+pub enum Match {
+    /// The answer is one of the accepted answers
+    Exact,
+    /// The answer was typed in Latin letters, in a deck with a transliteration scheme
+    Transliterated {
+        expected: String,
+    },
+    /// The answer is right apart from its diacritics, in a deck that ignores them
+    Diacritics {
+        expected: String,
+    },
+    /// The answer is a typo or two away from an accepted answer
+    Close {
+        expected: String,
+    },
+    Wrong,
+}
+
+/// How an answer was graded, saved with each review
+#[derive(Debug, Clone, Copy, PartialEq, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub enum Outcome {
+    Correct,
+    /// A near miss in a deck that records them separately, it isn't counted as correct but the
+    /// translation is still scheduled as remembered
+    Typo,
+    /// A correct answer given after asking for hints, it isn't counted as correct but the
+    /// translation is still scheduled as remembered
+    Hinted,
+    Incorrect,
+}
+
+impl Outcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Correct => "correct",
+            Outcome::Typo => "typo",
+            Outcome::Hinted => "hinted",
+            Outcome::Incorrect => "incorrect",
+        }
+    }
+
+    /// The grade a typed answer with this outcome is scheduled with
+    pub fn grade(self) -> Grade {
+        match self {
+            Outcome::Correct => Grade::Good,
+            Outcome::Typo | Outcome::Hinted => Grade::Hard,
+            Outcome::Incorrect => Grade::Again,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl ToSql<Text, Sqlite> for Outcome {
+    fn to_sql<W: Write>(&self, out: &mut Output<W, Sqlite>) -> serialize::Result {
+        <str as ToSql<Text, Sqlite>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Sqlite> for Outcome {
+    fn from_sql(bytes: Option<&<Sqlite as Backend>::RawValue>) -> deserialize::Result<Self> {
+        match <String as FromSql<Text, Sqlite>>::from_sql(bytes)?.as_str() {
+            "correct" => Ok(Outcome::Correct),
+            "typo" => Ok(Outcome::Typo),
+            "hinted" => Ok(Outcome::Hinted),
+            "incorrect" => Ok(Outcome::Incorrect),
+            other => Err(format!("Unrecognised outcome: {}", other).into()),
+        }
+    }
+}
+
+/// How a deck grades near misses
+#[derive(Debug, Clone, Copy, PartialEq, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub enum NearMiss {
+    Correct,
+    Wrong,
+    Typo,
+}
+
+impl NearMiss {
+    fn as_str(self) -> &'static str {
+        match self {
+            NearMiss::Correct => "correct",
+            NearMiss::Wrong => "wrong",
+            NearMiss::Typo => "typo",
+        }
+    }
+
+    /// The outcome of a near miss
+    pub fn outcome(self) -> Outcome {
+        match self {
+            NearMiss::Correct => Outcome::Correct,
+            NearMiss::Wrong => Outcome::Incorrect,
+            NearMiss::Typo => Outcome::Typo,
+        }
+    }
+}
+
+impl fmt::Display for NearMiss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for NearMiss {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "correct" => Ok(NearMiss::Correct),
+            "wrong" => Ok(NearMiss::Wrong),
+            "typo" => Ok(NearMiss::Typo),
+            other => Err(format!(
+                "Unrecognised near miss setting: {}, expected correct, wrong or typo",
+                other
+            )),
+        }
+    }
+}
+
+impl ToSql<Text, Sqlite> for NearMiss {
+    fn to_sql<W: Write>(&self, out: &mut Output<W, Sqlite>) -> serialize::Result {
+        <str as ToSql<Text, Sqlite>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Sqlite> for NearMiss {
+    fn from_sql(bytes: Option<&<Sqlite as Backend>::RawValue>) -> deserialize::Result<Self> {
+        Ok(<String as FromSql<Text, Sqlite>>::from_sql(bytes)?.parse()?)
+    }
+}
+
+/// The number of single character insertions, deletions, substitutions or swaps of neighbouring
+/// characters needed to turn one string into the other
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // distances[i][j] is the distance between the first i characters of a and first j of b
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + substitution);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// Puts text into a form that can be compared: compatibility normalised (NFKC), which also folds
+/// full and half width characters, and trimmed
+fn normalise(text: &str) -> String {
+    text.nfkc().collect::<String>().trim().to_string()
+}
+
+/// Unicode case folding, with the special rules for the dotted and dotless i of Turkish and
+/// Azerbaijani when the locale is one of theirs, eg `tr` or `az-AZ`
+pub fn fold_case(text: &str, locale: &str) -> String {
+    let language = locale.split(['-', '_']).next().unwrap_or("");
+    if language.eq_ignore_ascii_case("tr") || language.eq_ignore_ascii_case("az") {
+        let text: String = text
+            .chars()
+            .map(|c| match c {
+                'I' => 'ı',
+                'İ' => 'i',
+                c => c,
+            })
+            .collect();
+        caseless::default_case_fold_str(&text)
+    } else {
+        caseless::default_case_fold_str(text)
+    }
+}
+
+/// Whether two words are the same ignoring case, used to find words whatever case they are
+/// typed in
+pub fn same_word(a: &str, b: &str) -> bool {
+    fold_case(&normalise(a), "") == fold_case(&normalise(b), "")
+}
+
+/// Removes diacritics such as accents and umlauts, eg `café` becomes `cafe`. The voicing marks on
+/// Japanese kana are kept as they make a different sound rather than decorate the same one.
+fn strip_diacritics(text: &str) -> String {
+    text.nfd()
+        .filter(|&c| !is_combining_mark(c) || c == '\u{3099}' || c == '\u{309A}')
+        .nfc()
+        .collect()
+}
+
+/// How many typos an answer can have and still be a near miss. Short words don't get any, as a
+/// single typo could easily make them a different word.
+fn allowed_typos(expected: &str) -> usize {
+    match expected.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// How answers are compared, set for each deck
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Matcher {
+    /// Accept answers that are only missing (or have the wrong) diacritics
+    pub ignore_diacritics: bool,
+    /// Answers in the wrong case are wrong, or near misses if they are long enough
+    pub case_sensitive: bool,
+    /// The locale whose case folding rules are used, eg `tr`, or empty for the default rules
+    pub locale: String,
+    /// Lets answers in another script be typed in Latin letters
+    pub transliteration: Option<Transliteration>,
+}
+
+impl Matcher {
+    /// The matcher for answers in the local language. Case sensitivity, the locale and
+    /// transliteration only apply to the foreign language.
+    pub fn for_local(&self) -> Matcher {
+        Matcher {
+            ignore_diacritics: self.ignore_diacritics,
+            ..Matcher::default()
+        }
+    }
+
+    fn comparable(&self, text: &str) -> String {
+        let text = normalise(text);
+        if self.case_sensitive {
+            text
+        } else {
+            fold_case(&text, &self.locale)
+        }
+    }
+
+    /// Compares an answer to the accepted answers, each of which can be written with optional
+    /// parts and separators, see `parse_answers`
+    pub fn check<S: AsRef<str>>(&self, answer: &str, accepted: &[S]) -> Match {
+        let mut answer = self.comparable(answer);
+        // Each way of typing the accepted answers along with the form it is compared in
+        let mut forms: Vec<(String, String)> = accepted
+            .iter()
+            .flat_map(|a| variants(a.as_ref()))
+            .map(|variant| {
+                let form = self.comparable(&variant);
+                (variant, form)
+            })
+            .collect();
+        let find = |answer: &str, forms: &[(String, String)]| {
+            forms
+                .iter()
+                .find(|(_, form)| form == answer)
+                .map(|(expected, _)| expected.to_string())
+        };
+        if find(&answer, &forms).is_some() {
+            return Match::Exact;
+        }
+
+        if let Some(transliteration) = self.transliteration {
+            answer = transliteration.transliterate(&answer);
+            for (_, form) in forms.iter_mut() {
+                *form = transliteration.transliterate(form);
+            }
+            if let Some(expected) = find(&answer, &forms) {
+                return Match::Transliterated { expected };
+            }
+        }
+
+        if self.ignore_diacritics {
+            answer = strip_diacritics(&answer);
+            for (_, form) in forms.iter_mut() {
+                *form = strip_diacritics(form);
+            }
+            if let Some(expected) = find(&answer, &forms) {
+                return Match::Diacritics { expected };
+            }
+        }
+
+        let mut closest: Option<(usize, &str)> = None;
+        for (expected, form) in &forms {
+            let distance = edit_distance(&answer, form);
+            // is_none_or would need Rust 1.82, which nothing else here does
+            #[allow(clippy::unnecessary_map_or)]
+            let closer = closest.map_or(true, |(d, _)| distance < d);
+            if distance <= allowed_typos(form) && closer {
+                closest = Some((distance, expected));
+            }
+        }
+        match closest {
+            Some((_, expected)) => Match::Close {
+                expected: expected.to_string(),
+            },
+            None => Match::Wrong,
+        }
+    }
+}
+
+/// Compares an answer to the accepted answers with the default matcher
+pub fn check_answer<S: AsRef<str>>(answer: &str, accepted: &[S]) -> Match {
+    Matcher::default().check(answer, accepted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_answer, edit_distance, fold_case, Match, Matcher};
+    use crate::vocab_store::transliteration::Transliteration;
+
+    #[test]
+    fn test_syntax() {
+        assert_eq!(check_answer("eat", &["(to) eat"]), Match::Exact);
+        assert_eq!(
+            check_answer("automobile", &["car / automobile"]),
+            Match::Exact
+        );
+        assert_eq!(check_answer("km/h", &["km/h"]), Match::Exact);
+        assert_eq!(
+            check_answer("automobil", &["car; automobile"]),
+            Match::Close {
+                expected: "automobile".to_string()
+            }
+        );
+        assert_eq!(
+            check_answer("car; automobile", &["car; automobile"]),
+            Match::Wrong
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("haus", "haus"), 0);
+        assert_eq!(edit_distance("haus", "hause"), 1);
+        assert_eq!(edit_distance("haus", "hasu"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "はい"), 2);
+    }
+
+    #[test]
+    fn test_check() {
+        let accepted = ["yes", "morning"];
+        assert_eq!(check_answer(" Yes", &accepted), Match::Exact);
+        assert_eq!(check_answer("yse", &accepted), Match::Wrong);
+        assert_eq!(
+            check_answer("mornign", &accepted),
+            Match::Close {
+                expected: "morning".to_string()
+            }
+        );
+        assert_eq!(check_answer("evening", &accepted), Match::Wrong);
+    }
+
+    #[test]
+    fn test_normalises() {
+        // The decomposed é from some keyboards against the composed one
+        assert_eq!(check_answer("cafe\u{301}", &["café"]), Match::Exact);
+        // Full width letters and half width katakana from a Japanese IME
+        assert_eq!(check_answer("ｔｏｋｙｏ", &["tokyo"]), Match::Exact);
+        assert_eq!(check_answer("ﾊﾟﾝ", &["パン"]), Match::Exact);
+    }
+
+    #[test]
+    fn test_ignore_diacritics() {
+        let matcher = Matcher {
+            ignore_diacritics: true,
+            ..Matcher::default()
+        };
+        // Without the option a missing accent is only a near miss
+        assert_eq!(
+            check_answer("cafe", &["café"]),
+            Match::Close {
+                expected: "café".to_string()
+            }
+        );
+        assert_eq!(
+            matcher.check("Cafe", &["café"]),
+            Match::Diacritics {
+                expected: "café".to_string()
+            }
+        );
+        assert_eq!(
+            matcher.check("schon", &["schön"]),
+            Match::Diacritics {
+                expected: "schön".to_string()
+            }
+        );
+        // Kana voicing marks aren't diacritics
+        assert_eq!(matcher.check("か", &["が"]), Match::Wrong);
+        assert_eq!(
+            matcher.check("cafes", &["café"]),
+            Match::Close {
+                expected: "café".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_fold_case() {
+        assert_eq!(fold_case("Straße", ""), "strasse");
+        assert_eq!(fold_case("İstanbul", "tr"), "istanbul");
+        assert_eq!(fold_case("KIŞ", "tr-TR"), "kış");
+        assert_eq!(fold_case("KIŞ", "en"), "kiş");
+    }
+
+    #[test]
+    fn test_case() {
+        assert_eq!(check_answer("STRASSE", &["Straße"]), Match::Exact);
+        assert_eq!(
+            check_answer("ıstanbul", &["Istanbul"]),
+            Match::Close {
+                expected: "Istanbul".to_string()
+            }
+        );
+        let turkish = Matcher {
+            locale: "tr".to_string(),
+            ..Matcher::default()
+        };
+        assert_eq!(turkish.check("ıstanbul", &["Istanbul"]), Match::Exact);
+        assert_eq!(turkish.for_local().check("i", &["I"]), Match::Exact);
+
+        let case_sensitive = Matcher {
+            case_sensitive: true,
+            ..Matcher::default()
+        };
+        assert_eq!(case_sensitive.check("Haus", &["Haus"]), Match::Exact);
+        assert_eq!(
+            case_sensitive.check("haus", &["Haus"]),
+            Match::Close {
+                expected: "Haus".to_string()
+            }
+        );
+        assert_eq!(case_sensitive.check("sie", &["Sie"]), Match::Wrong);
+    }
+
+    #[test]
+    fn test_transliteration() {
+        let romaji = Matcher {
+            transliteration: Some(Transliteration::Romaji),
+            ..Matcher::default()
+        };
+        assert_eq!(check_answer("nihon", &["にほん"]), Match::Wrong);
+        assert_eq!(
+            romaji.check("Nihon", &["日本", "にほん"]),
+            Match::Transliterated {
+                expected: "にほん".to_string()
+            }
+        );
+        assert_eq!(
+            romaji.check("pan", &["パン"]),
+            Match::Transliterated {
+                expected: "パン".to_string()
+            }
+        );
+        assert_eq!(
+            romaji.check("konnichiha", &["こんにちは"]),
+            Match::Transliterated {
+                expected: "こんにちは".to_string()
+            }
+        );
+        assert_eq!(
+            romaji.check("konnichiwa", &["こんにちは"]),
+            Match::Close {
+                expected: "こんにちは".to_string()
+            }
+        );
+        assert_eq!(romaji.for_local().check("nihon", &["にほん"]), Match::Wrong);
+    }
+}
@@ -0,0 +1,322 @@
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use diesel::sqlite::Sqlite;
+use diesel::{
+    Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SqliteConnection,
+};
+
+use crate::schema::{decks, metadata, translations};
+use crate::vocab_store::answer::{Matcher, NearMiss};
+use crate::vocab_store::tags::delete_unused_tags;
+use crate::vocab_store::transliteration::Transliteration;
+use crate::vocab_store::VSResult;
+use crate::{VocabStore, VocabStoreError};
+
+const CURRENT_DECK: &str = "current_deck";
+
+/// When the reading of a foreign word is shown
+#[derive(Debug, Clone, Copy, PartialEq, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub enum ShowReading {
+    /// Alongside the foreign word whenever it's shown, including in the prompt
+    Prompt,
+    /// Only once the guess has been answered
+    Answer,
+}
+
+impl ShowReading {
+    fn as_str(self) -> &'static str {
+        match self {
+            ShowReading::Prompt => "prompt",
+            ShowReading::Answer => "answer",
+        }
+    }
+}
+
+impl fmt::Display for ShowReading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ShowReading {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "prompt" => Ok(ShowReading::Prompt),
+            "answer" => Ok(ShowReading::Answer),
+            other => Err(format!(
+                "Unrecognised reading setting: {}, expected prompt or answer",
+                other
+            )),
+        }
+    }
+}
+
+impl ToSql<Text, Sqlite> for ShowReading {
+    fn to_sql<W: Write>(&self, out: &mut Output<W, Sqlite>) -> serialize::Result {
+        <str as ToSql<Text, Sqlite>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Sqlite> for ShowReading {
+    fn from_sql(bytes: Option<&<Sqlite as Backend>::RawValue>) -> deserialize::Result<Self> {
+        Ok(<String as FromSql<Text, Sqlite>>::from_sql(bytes)?.parse()?)
+    }
+}
+
+/// A named set of translations between two languages, eg `japanese`
+#[derive(Debug, Clone, PartialEq, Queryable, Identifiable, AsChangeset)]
+#[table_name = "decks"]
+pub struct Deck {
+    pub id: i32,
+    pub name: String,
+    /// What the local language is called, empty if it hasn't been given
+    pub local_language: String,
+    /// What the foreign language is called, empty if it hasn't been given
+    pub foreign_language: String,
+    /// When the readings of foreign words are shown
+    pub show_reading: ShowReading,
+    /// How answers that are only a typo away from being right are graded
+    pub near_miss: NearMiss,
+    /// Whether answers are accepted without their diacritics, eg `cafe` for `café`
+    pub ignore_diacritics: bool,
+    /// Whether the case of foreign answers matters, eg for German nouns
+    pub case_sensitive: bool,
+    /// The locale of the foreign language, used for its case rules and articles, eg `tr` for
+    /// Turkish. Empty if the default rules are fine.
+    pub locale: String,
+    /// How foreign answers can be typed in Latin letters, if they can
+    pub transliteration: Option<Transliteration>,
+    /// Whether to ask if the answer was right after every wrong answer, rather than only after
+    /// near misses
+    pub overrule_wrong: bool,
+}
+
+impl Deck {
+    /// How answers are compared in this deck
+    pub fn matcher(&self) -> Matcher {
+        Matcher {
+            ignore_diacritics: self.ignore_diacritics,
+            case_sensitive: self.case_sensitive,
+            locale: self.locale.clone(),
+            transliteration: self.transliteration,
+        }
+    }
+
+    /// The foreign language as far as its grammar goes, eg for its articles. This is the locale
+    /// if one has been given, or else the name of the foreign language.
+    pub fn language(&self) -> &str {
+        if self.locale.is_empty() {
+            &self.foreign_language
+        } else {
+            &self.locale
+        }
+    }
+}
+
+/// The deck that was last used, or the oldest deck if one was never chosen or it has since been
+/// deleted
+pub fn current_deck_id(conn: &SqliteConnection) -> VSResult<i32> {
+    let remembered = metadata::table
+        .select(metadata::value)
+        .filter(metadata::key.eq(CURRENT_DECK))
+        .first::<String>(conn)
+        .optional()?
+        .and_then(|id| id.parse::<i32>().ok());
+    if let Some(id) = remembered {
+        let exists = decks::table
+            .find(id)
+            .select(decks::id)
+            .first::<i32>(conn)
+            .optional()?;
+        if exists.is_some() {
+            return Ok(id);
+        }
+    }
+    decks::table
+        .select(decks::id)
+        .order(decks::id)
+        .first::<i32>(conn)
+        .optional()?
+        .ok_or(VocabStoreError::DeckNotFound)
+}
+
+impl VocabStore {
+    pub fn create_deck(
+        &self,
+        name: &str,
+        local_language: &str,
+        foreign_language: &str,
+    ) -> VSResult<Deck> {
+        let name = name.trim();
+        if self.find_deck(name)?.is_some() {
+            return Err(VocabStoreError::DeckAlreadyExists);
+        }
+        diesel::insert_into(decks::table)
+            .values((
+                decks::name.eq(name),
+                decks::local_language.eq(local_language.trim()),
+                decks::foreign_language.eq(foreign_language.trim()),
+            ))
+            .execute(&self.conn)?;
+        self.find_deck(name)?.ok_or(VocabStoreError::DeckNotFound)
+    }
+
+    /// Every deck, in alphabetical order
+    pub fn decks(&self) -> VSResult<Vec<Deck>> {
+        Ok(decks::table.order(decks::name).load::<Deck>(&self.conn)?)
+    }
+
+    pub fn find_deck(&self, name: &str) -> VSResult<Option<Deck>> {
+        Ok(decks::table
+            .filter(decks::name.eq(name.trim()))
+            .first::<Deck>(&self.conn)
+            .optional()?)
+    }
+
+    /// The deck that translations are being added to and read from
+    pub fn current_deck(&self) -> VSResult<Deck> {
+        Ok(decks::table.find(self.deck_id).first::<Deck>(&self.conn)?)
+    }
+
+    /// Switches to another deck, which will still be used the next time the store is opened
+    pub fn use_deck(&mut self, name: &str) -> VSResult<Deck> {
+        let deck = self.find_deck(name)?.ok_or(VocabStoreError::DeckNotFound)?;
+        diesel::replace_into(metadata::table)
+            .values((
+                metadata::key.eq(CURRENT_DECK),
+                metadata::value.eq(deck.id.to_string()),
+            ))
+            .execute(&self.conn)?;
+        self.deck_id = deck.id;
+        Ok(deck)
+    }
+
+    /// Saves changes to a deck's settings
+    pub fn save_deck(&self, deck: &Deck) -> VSResult<()> {
+        diesel::update(deck).set(deck).execute(&self.conn)?;
+        Ok(())
+    }
+
+    /// Deletes a deck along with every translation in it. The current deck can't be deleted.
+    pub fn delete_deck(&self, name: &str) -> VSResult<()> {
+        let deck = self.find_deck(name)?.ok_or(VocabStoreError::DeckNotFound)?;
+        if deck.id == self.deck_id {
+            return Err(VocabStoreError::DeckInUse);
+        }
+        self.conn.transaction(|| {
+            diesel::delete(decks::table.find(deck.id)).execute(&self.conn)?;
+            delete_unused_tags(&self.conn)
+        })
+    }
+
+    /// How many translations are in a deck
+    pub fn deck_size(&self, deck: &Deck) -> VSResult<i64> {
+        Ok(translations::table
+            .filter(translations::deck_id.eq(deck.id))
+            .count()
+            .get_result::<i64>(&self.conn)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{NearMiss, ShowReading, Translation, Transliteration, VocabStore, VocabStoreError};
+
+    const TEST_FILE: &str = "test.sqlite";
+
+    #[test]
+    fn test_starts_with_default_deck() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        let deck = vocab_store.current_deck().unwrap();
+        assert_eq!(deck.name, "default");
+        assert_eq!(deck.show_reading, ShowReading::Answer);
+        assert_eq!(vocab_store.decks().unwrap(), vec![deck]);
+    }
+
+    #[test]
+    fn test_save_deck() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        let mut deck = vocab_store.current_deck().unwrap();
+        deck.show_reading = ShowReading::Prompt;
+        deck.near_miss = NearMiss::Typo;
+        deck.ignore_diacritics = true;
+        deck.case_sensitive = true;
+        deck.overrule_wrong = true;
+        deck.locale = "tr".to_string();
+        deck.transliteration = Some(Transliteration::Romaji);
+        vocab_store.save_deck(&deck).unwrap();
+        assert_eq!(vocab_store.current_deck().unwrap(), deck);
+    }
+
+    #[test]
+    fn test_decks_are_separate() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let mut vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        vocab_store.add(&Translation::new("yes", "はい")).unwrap();
+
+        let spanish = vocab_store
+            .create_deck("spanish", "English", "Spanish")
+            .unwrap();
+        assert_eq!(spanish.foreign_language, "Spanish");
+        vocab_store.use_deck("spanish").unwrap();
+        assert!(vocab_store.find_local("yes").unwrap().is_empty());
+        vocab_store.add(&Translation::new("yes", "sí")).unwrap();
+        assert_eq!(vocab_store.entries().count(), 1);
+        assert_eq!(vocab_store.deck_size(&spanish).unwrap(), 1);
+
+        // The deck is remembered
+        let mut vocab_store = VocabStore::from(TEST_FILE).unwrap();
+        assert_eq!(vocab_store.current_deck().unwrap(), spanish);
+        assert_eq!(vocab_store.find_local("yes").unwrap()[0].foreign, "sí");
+
+        vocab_store.use_deck("default").unwrap();
+        assert_eq!(vocab_store.find_local("yes").unwrap()[0].foreign, "はい");
+    }
+
+    #[test]
+    fn test_deck_errors() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let mut vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        match vocab_store.create_deck("default", "", "") {
+            Err(VocabStoreError::DeckAlreadyExists) => {}
+            _ => panic!("VocabStore did not return DeckAlreadyExists error"),
+        }
+        match vocab_store.use_deck("missing") {
+            Err(VocabStoreError::DeckNotFound) => {}
+            _ => panic!("VocabStore did not return DeckNotFound error"),
+        }
+        match vocab_store.delete_deck("default") {
+            Err(VocabStoreError::DeckInUse) => {}
+            _ => panic!("VocabStore did not return DeckInUse error"),
+        }
+    }
+
+    #[test]
+    fn test_delete_deck() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let mut vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        vocab_store.create_deck("spanish", "", "").unwrap();
+        vocab_store.use_deck("spanish").unwrap();
+        vocab_store.add(&Translation::new("bread", "pan")).unwrap();
+        let bread = vocab_store.find("bread", "pan").unwrap().unwrap();
+        vocab_store.add_tag(&bread, "food").unwrap();
+
+        vocab_store.use_deck("default").unwrap();
+        vocab_store.delete_deck("spanish").unwrap();
+        assert!(vocab_store.find_deck("spanish").unwrap().is_none());
+        assert!(vocab_store.tags().unwrap().is_empty());
+    }
+}
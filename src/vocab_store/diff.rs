@@ -0,0 +1,166 @@
+//! Lines up a typed answer with the expected one to show where they differ. Answers are split into
+//! graphemes, what a reader sees as a single character, so a kanji or a letter with a combining
+//! accent is never split in two.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::vocab_store::answer::{edit_distance, same_word};
+use crate::vocab_store::syntax::variants;
+
+/// How one grapheme of a typed answer lines up with the expected answer
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// Typed as expected, ignoring case
+    Same { typed: String, expected: String },
+    /// Typed in place of the expected grapheme
+    Changed { typed: String, expected: String },
+    /// Expected but not typed
+    Missing(String),
+    /// Typed but not expected
+    Extra(String),
+}
+
+/// The fewest differences that turn the typed answer into the expected one, in order
+pub fn diff(typed: &str, expected: &str) -> Vec<Difference> {
+    let typed: Vec<&str> = typed.trim().graphemes(true).collect();
+    let expected: Vec<&str> = expected.trim().graphemes(true).collect();
+    // costs[i][j] is the number of differences between the first i graphemes of typed and the
+    // first j of expected
+    let mut costs = vec![vec![0; expected.len() + 1]; typed.len() + 1];
+    for (i, row) in costs.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cost) in costs[0].iter_mut().enumerate() {
+        *cost = j;
+    }
+    for i in 1..=typed.len() {
+        for j in 1..=expected.len() {
+            let changed = usize::from(!same_word(typed[i - 1], expected[j - 1]));
+            costs[i][j] = (costs[i - 1][j - 1] + changed)
+                .min(costs[i - 1][j] + 1)
+                .min(costs[i][j - 1] + 1);
+        }
+    }
+
+    // Walk back from the end, preferring to line graphemes up over adding or dropping them
+    let mut differences = Vec::new();
+    let (mut i, mut j) = (typed.len(), expected.len());
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 {
+            let same = same_word(typed[i - 1], expected[j - 1]);
+            if costs[i][j] == costs[i - 1][j - 1] + usize::from(!same) {
+                let typed = typed[i - 1].to_string();
+                let expected = expected[j - 1].to_string();
+                differences.push(if same {
+                    Difference::Same { typed, expected }
+                } else {
+                    Difference::Changed { typed, expected }
+                });
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+        }
+        if i > 0 && costs[i][j] == costs[i - 1][j] + 1 {
+            differences.push(Difference::Extra(typed[i - 1].to_string()));
+            i -= 1;
+        } else {
+            differences.push(Difference::Missing(expected[j - 1].to_string()));
+            j -= 1;
+        }
+    }
+    differences.reverse();
+    differences
+}
+
+/// The way of typing one of the accepted answers that is closest to the typed answer, to show
+/// the differences against
+pub fn closest_answer<S: AsRef<str>>(typed: &str, accepted: &[S]) -> Option<String> {
+    accepted
+        .iter()
+        .flat_map(|answer| variants(answer.as_ref()))
+        .min_by_key(|answer| edit_distance(typed, answer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{closest_answer, diff, Difference};
+
+    fn same(text: &str) -> Difference {
+        Difference::Same {
+            typed: text.to_string(),
+            expected: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(
+            diff("Haus", "haus"),
+            vec![
+                Difference::Same {
+                    typed: "H".to_string(),
+                    expected: "h".to_string()
+                },
+                same("a"),
+                same("u"),
+                same("s")
+            ]
+        );
+        assert_eq!(
+            diff("hause", "haus"),
+            vec![
+                same("h"),
+                same("a"),
+                same("u"),
+                same("s"),
+                Difference::Extra("e".to_string())
+            ]
+        );
+        assert_eq!(
+            diff("hus", "haus"),
+            vec![
+                same("h"),
+                Difference::Missing("a".to_string()),
+                same("u"),
+                same("s")
+            ]
+        );
+        assert_eq!(
+            diff("こんにちわ", "こんにちは"),
+            vec![
+                same("こ"),
+                same("ん"),
+                same("に"),
+                same("ち"),
+                Difference::Changed {
+                    typed: "わ".to_string(),
+                    expected: "は".to_string()
+                }
+            ]
+        );
+        // A letter with a combining accent is a single grapheme
+        assert_eq!(
+            diff("cafe", "cafe\u{301}"),
+            vec![
+                same("c"),
+                same("a"),
+                same("f"),
+                Difference::Changed {
+                    typed: "e".to_string(),
+                    expected: "e\u{301}".to_string()
+                }
+            ]
+        );
+        assert_eq!(diff("", "a"), vec![Difference::Missing("a".to_string())]);
+    }
+
+    #[test]
+    fn test_closest_answer() {
+        assert_eq!(
+            closest_answer("automobil", &["(to) drive", "car; automobile"]),
+            Some("automobile".to_string())
+        );
+        assert_eq!(closest_answer::<&str>("car", &[]), None);
+    }
+}
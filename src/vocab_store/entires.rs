@@ -1,14 +1,28 @@
+use crate::vocab_store::deck_translations;
 use crate::{Translation, VocabStoreError};
 use diesel::{QueryDsl, RunQueryDsl, SqliteConnection};
 
 pub struct Entries<'c> {
     conn: &'c SqliteConnection,
+    deck: i32,
     page: i64,
+    tag: Option<&'c str>,
 }
 
 impl<'c> Entries<'c> {
-    pub fn new(conn: &'c SqliteConnection) -> Entries<'c> {
-        Entries { conn, page: 0 }
+    pub fn new(conn: &'c SqliteConnection, deck: i32) -> Entries<'c> {
+        Entries {
+            conn,
+            deck,
+            page: 0,
+            tag: None,
+        }
+    }
+
+    /// Only return translations with the given tag
+    pub fn with_tag(mut self, tag: Option<&'c str>) -> Entries<'c> {
+        self.tag = tag;
+        self
     }
 }
 
@@ -18,7 +32,8 @@ impl<'c> Iterator for Entries<'c> {
     fn next(&mut self) -> Option<Self::Item> {
         use crate::schema::translations::dsl::*;
 
-        let query_result = translations
+        let query_result = deck_translations(self.deck, self.tag)
+            .order(id)
             .limit(1)
             .offset(self.page)
             .load::<Translation>(self.conn);
@@ -35,6 +50,7 @@ impl<'c> Iterator for Entries<'c> {
 #[cfg(test)]
 mod tests {
     use super::Entries;
+    use crate::vocab_store::translation::NewTranslation;
     use crate::{Translation, VocabStore};
     use diesel::{Connection, RunQueryDsl, SqliteConnection};
     use std::fs;
@@ -43,21 +59,25 @@ mod tests {
 
     #[test]
     fn test_exports_all_translation() {
-        let _ = fs::remove_file(&TEST_FILE); // Ok if it fails;
-        let vocab_store = VocabStore::init(&TEST_FILE).unwrap(); // Init DB
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        VocabStore::init(TEST_FILE).unwrap(); // Init DB
 
-        let conn = SqliteConnection::establish(&TEST_FILE).unwrap();
-        let mut exporter = Entries::new(&conn);
-        let translation_yes = Translation::new("yes", "はい");
-        let translation_no = Translation::new("no", "いいえ");
+        let conn = SqliteConnection::establish(TEST_FILE).unwrap();
+        let mut exporter = Entries::new(&conn, 1);
+        let mut translation_yes = Translation::new("yes", "はい");
+        let mut translation_no = Translation::new("no", "いいえ");
+        translation_yes.deck_id = 1;
+        translation_no.deck_id = 1;
         diesel::insert_into(crate::schema::translations::table)
-            .values(&translation_yes)
+            .values(NewTranslation::from(&translation_yes))
             .execute(&conn)
             .unwrap();
         diesel::insert_into(crate::schema::translations::table)
-            .values(&translation_no)
+            .values(NewTranslation::from(&translation_no))
             .execute(&conn)
             .unwrap();
+        translation_yes.id = 1;
+        translation_no.id = 2;
 
         assert_eq!(exporter.next().unwrap().unwrap(), translation_yes);
         assert_eq!(exporter.next().unwrap().unwrap(), translation_no);
@@ -0,0 +1,36 @@
+use crate::vocab_store::list::{List, ListKind};
+
+/// Sentences showing how a translation is used.
+///
+/// Stored (and exported) as a single string with a sentence on each line.
+pub type Examples = List<Example>;
+
+pub struct Example;
+
+impl ListKind for Example {
+    const SEPARATOR: char = '\n';
+    const REPLACEMENT: &'static str = " ";
+
+    /// Unlike alternates the case of examples is kept
+    fn same(a: &str, b: &str) -> bool {
+        a == b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Examples;
+
+    #[test]
+    fn test_add_and_round_trip() {
+        let mut examples = Examples::default();
+        assert!(examples.add(" Yes, I know. "));
+        assert!(examples.add("はい、\nそうです。"));
+        assert!(!examples.add("Yes, I know."));
+        assert!(!examples.add(""));
+        assert_eq!(*examples, vec!["Yes, I know.", "はい、 そうです。"]);
+
+        assert_eq!(examples.to_string().parse::<Examples>().unwrap(), examples);
+        assert!("".parse::<Examples>().unwrap().is_empty());
+    }
+}
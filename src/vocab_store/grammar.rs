@@ -0,0 +1,316 @@
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use diesel::sqlite::Sqlite;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::schema::translations;
+use crate::vocab_store::{deck_translations, VSResult};
+use crate::{Translation, VocabStore};
+
+/// What kind of word a translation is
+#[derive(Debug, Clone, Copy, PartialEq, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub enum PartOfSpeech {
+    Noun,
+    Verb,
+    Adjective,
+    Adverb,
+    Pronoun,
+    Preposition,
+    Conjunction,
+    Interjection,
+    /// More than one word, eg an expression or idiom
+    Phrase,
+}
+
+impl PartOfSpeech {
+    fn as_str(self) -> &'static str {
+        match self {
+            PartOfSpeech::Noun => "noun",
+            PartOfSpeech::Verb => "verb",
+            PartOfSpeech::Adjective => "adjective",
+            PartOfSpeech::Adverb => "adverb",
+            PartOfSpeech::Pronoun => "pronoun",
+            PartOfSpeech::Preposition => "preposition",
+            PartOfSpeech::Conjunction => "conjunction",
+            PartOfSpeech::Interjection => "interjection",
+            PartOfSpeech::Phrase => "phrase",
+        }
+    }
+}
+
+impl fmt::Display for PartOfSpeech {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for PartOfSpeech {
+    type Err = String;
+
+    /// Accepts the full name or a common abbreviation, eg `adj`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "noun" | "n" => Ok(PartOfSpeech::Noun),
+            "verb" | "v" => Ok(PartOfSpeech::Verb),
+            "adjective" | "adj" => Ok(PartOfSpeech::Adjective),
+            "adverb" | "adv" => Ok(PartOfSpeech::Adverb),
+            "pronoun" | "pron" => Ok(PartOfSpeech::Pronoun),
+            "preposition" | "prep" => Ok(PartOfSpeech::Preposition),
+            "conjunction" | "conj" => Ok(PartOfSpeech::Conjunction),
+            "interjection" | "interj" => Ok(PartOfSpeech::Interjection),
+            "phrase" => Ok(PartOfSpeech::Phrase),
+            other => Err(format!("Unrecognised part of speech: {}", other)),
+        }
+    }
+}
+
+impl ToSql<Text, Sqlite> for PartOfSpeech {
+    fn to_sql<W: Write>(&self, out: &mut Output<W, Sqlite>) -> serialize::Result {
+        <str as ToSql<Text, Sqlite>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Sqlite> for PartOfSpeech {
+    fn from_sql(bytes: Option<&<Sqlite as Backend>::RawValue>) -> deserialize::Result<Self> {
+        Ok(<String as FromSql<Text, Sqlite>>::from_sql(bytes)?.parse()?)
+    }
+}
+
+/// The grammatical gender of a noun
+#[derive(Debug, Clone, Copy, PartialEq, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub enum Gender {
+    Masculine,
+    Feminine,
+    Neuter,
+    /// Masculine and feminine merged into one, eg Dutch and Swedish
+    Common,
+}
+
+/// The genders of a language, each with its definite article
+pub type Articles = &'static [(Gender, &'static str)];
+
+/// The definite articles of the languages vocab knows about, used to ask "der, die or das?"
+/// rather than "masculine, feminine or neuter?". Each language has its code and English name.
+const ARTICLES: &[(&str, &str, Articles)] = &[
+    (
+        "de",
+        "german",
+        &[
+            (Gender::Masculine, "der"),
+            (Gender::Feminine, "die"),
+            (Gender::Neuter, "das"),
+        ],
+    ),
+    (
+        "fr",
+        "french",
+        &[(Gender::Masculine, "le"), (Gender::Feminine, "la")],
+    ),
+    (
+        "es",
+        "spanish",
+        &[(Gender::Masculine, "el"), (Gender::Feminine, "la")],
+    ),
+    (
+        "it",
+        "italian",
+        &[(Gender::Masculine, "il"), (Gender::Feminine, "la")],
+    ),
+    (
+        "pt",
+        "portuguese",
+        &[(Gender::Masculine, "o"), (Gender::Feminine, "a")],
+    ),
+    (
+        "nl",
+        "dutch",
+        &[(Gender::Common, "de"), (Gender::Neuter, "het")],
+    ),
+    (
+        "sv",
+        "swedish",
+        &[(Gender::Common, "en"), (Gender::Neuter, "ett")],
+    ),
+];
+
+impl Gender {
+    fn as_str(self) -> &'static str {
+        match self {
+            Gender::Masculine => "masculine",
+            Gender::Feminine => "feminine",
+            Gender::Neuter => "neuter",
+            Gender::Common => "common",
+        }
+    }
+
+    /// The genders nouns can have in a language along with their definite articles, if vocab
+    /// knows them. The language is a locale, eg `de` or `de-AT`, or failing that its English
+    /// name, eg `German`.
+    pub fn articles(language: &str) -> Option<Articles> {
+        let language = language.trim().to_lowercase();
+        let code = language.split(['-', '_']).next().unwrap_or_default();
+        ARTICLES
+            .iter()
+            .find(|(known_code, name, _)| *known_code == code || *name == language)
+            .map(|(_, _, articles)| *articles)
+    }
+
+    /// Whether nouns in a language can have this gender, eg German has no common nouns. Any
+    /// gender is allowed in languages vocab doesn't know the articles of.
+    pub fn in_language(self, language: &str) -> bool {
+        match Gender::articles(language) {
+            Some(articles) => articles.iter().any(|(gender, _)| *gender == self),
+            None => true,
+        }
+    }
+
+    /// The definite article for this gender in a language, eg `das` for a neuter German noun
+    pub fn article(self, language: &str) -> Option<&'static str> {
+        Gender::articles(language)?
+            .iter()
+            .find(|(gender, _)| *gender == self)
+            .map(|(_, article)| *article)
+    }
+
+    /// Whether an answer names this gender, either by its article in the language, its name or
+    /// its first letter
+    pub fn accepts(self, answer: &str, language: &str) -> bool {
+        let answer = answer.trim().to_lowercase();
+        self.article(language) == Some(answer.as_str()) || answer.parse() == Ok(self)
+    }
+}
+
+impl fmt::Display for Gender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Gender {
+    type Err = String;
+
+    /// Accepts the full name or its first letter, eg `m`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "masculine" | "m" => Ok(Gender::Masculine),
+            "feminine" | "f" => Ok(Gender::Feminine),
+            "neuter" | "n" => Ok(Gender::Neuter),
+            "common" | "c" => Ok(Gender::Common),
+            other => Err(format!("Unrecognised gender: {}", other)),
+        }
+    }
+}
+
+impl ToSql<Text, Sqlite> for Gender {
+    fn to_sql<W: Write>(&self, out: &mut Output<W, Sqlite>) -> serialize::Result {
+        <str as ToSql<Text, Sqlite>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Sqlite> for Gender {
+    fn from_sql(bytes: Option<&<Sqlite as Backend>::RawValue>) -> deserialize::Result<Self> {
+        Ok(<String as FromSql<Text, Sqlite>>::from_sql(bytes)?.parse()?)
+    }
+}
+
+impl VocabStore {
+    /// Every translation in the current deck that is a given part of speech, optionally only
+    /// those with a tag
+    pub fn find_part_of_speech(
+        &self,
+        part_of_speech: PartOfSpeech,
+        tag: Option<&str>,
+    ) -> VSResult<Vec<Translation>> {
+        Ok(deck_translations(self.deck_id, tag)
+            .filter(translations::part_of_speech.eq(part_of_speech))
+            .order(translations::id)
+            .load::<Translation>(&self.conn)?)
+    }
+
+    /// Every translation in the current deck that has a gender, optionally only those with a tag
+    pub fn find_gendered(&self, tag: Option<&str>) -> VSResult<Vec<Translation>> {
+        Ok(deck_translations(self.deck_id, tag)
+            .filter(translations::gender.is_not_null())
+            .order(translations::id)
+            .load::<Translation>(&self.conn)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{Gender, PartOfSpeech};
+    use crate::{Translation, VocabStore};
+
+    const TEST_FILE: &str = "test.sqlite";
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("Adj".parse(), Ok(PartOfSpeech::Adjective));
+        assert_eq!("noun".parse(), Ok(PartOfSpeech::Noun));
+        assert!("article".parse::<PartOfSpeech>().is_err());
+        assert_eq!("n".parse(), Ok(Gender::Neuter));
+        assert_eq!(" Feminine".parse(), Ok(Gender::Feminine));
+    }
+
+    #[test]
+    fn test_articles() {
+        assert_eq!(Gender::Neuter.article("German"), Some("das"));
+        assert_eq!(Gender::Neuter.article("French"), None);
+        assert_eq!(Gender::Common.article("dutch"), Some("de"));
+        assert_eq!(Gender::Neuter.article("de"), Some("das"));
+        assert_eq!(Gender::Feminine.article("de-AT"), Some("die"));
+        assert_eq!(Gender::Common.article("sv_FI"), Some("en"));
+        assert!(Gender::articles("Japanese").is_none());
+        assert!(Gender::articles("Deutsch").is_none());
+        assert!(!Gender::Common.in_language("de"));
+        assert!(Gender::Neuter.in_language("nl"));
+        assert!(Gender::Common.in_language("ja"));
+    }
+
+    #[test]
+    fn test_accepts() {
+        assert!(Gender::Feminine.accepts("Die", "German"));
+        assert!(Gender::Feminine.accepts("f", "German"));
+        assert!(!Gender::Feminine.accepts("das", "German"));
+        assert!(Gender::Masculine.accepts("masculine", "Klingon"));
+    }
+
+    #[test]
+    fn test_find_by_grammar() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        let mut house = Translation::new("house", "haus");
+        house.part_of_speech = Some(PartOfSpeech::Noun);
+        house.gender = Some(Gender::Neuter);
+        house.plural = Some("häuser".to_string());
+        vocab_store.add(&house).unwrap();
+        let mut go = Translation::new("go", "gehen");
+        go.part_of_speech = Some(PartOfSpeech::Verb);
+        vocab_store.add(&go).unwrap();
+
+        let nouns = vocab_store
+            .find_part_of_speech(PartOfSpeech::Noun, None)
+            .unwrap();
+        assert_eq!(nouns.len(), 1);
+        assert_eq!(nouns[0].gender, Some(Gender::Neuter));
+        assert_eq!(nouns[0].plural.as_deref(), Some("häuser"));
+
+        let gendered: Vec<_> = vocab_store
+            .find_gendered(None)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.local)
+            .collect();
+        assert_eq!(gendered, vec!["house"]);
+    }
+}
@@ -1,32 +1,335 @@
+use std::io::Write;
 use std::ops::Deref;
+use std::time::{Duration, Instant};
 
+use chrono::{NaiveDateTime, Utc};
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use diesel::sqlite::Sqlite;
+
+use crate::vocab_store::answer::{Match, Matcher, NearMiss, Outcome};
+use crate::vocab_store::scheduler::Grade;
+use crate::vocab_store::syntax::variants;
 use crate::Translation;
 
-pub enum Guess {
-    Local(Translation),
-    Foreign(Translation),
+/// Which side of the translation the user is being asked for
+#[derive(Debug, Clone, Copy, PartialEq, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub enum Direction {
+    /// Shown the foreign word, asked for the local one
+    Local,
+    /// Shown the local word, asked for the foreign one
+    Foreign,
+}
+
+impl Direction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Direction::Local => "local",
+            Direction::Foreign => "foreign",
+        }
+    }
+}
+
+impl ToSql<Text, Sqlite> for Direction {
+    fn to_sql<W: Write>(&self, out: &mut Output<W, Sqlite>) -> serialize::Result {
+        <str as ToSql<Text, Sqlite>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Sqlite> for Direction {
+    fn from_sql(bytes: Option<&<Sqlite as Backend>::RawValue>) -> deserialize::Result<Self> {
+        match <String as FromSql<Text, Sqlite>>::from_sql(bytes)?.as_str() {
+            "local" => Ok(Direction::Local),
+            "foreign" => Ok(Direction::Foreign),
+            other => Err(format!("Unrecognised direction: {}", other).into()),
+        }
+    }
+}
+
+/// A single answer given to a guess, waiting to be saved to the review log
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attempt {
+    pub reviewed_at: NaiveDateTime,
+    pub direction: Direction,
+    pub answer: String,
+    pub correct: bool,
+    pub response_time: Duration,
+    pub outcome: Outcome,
+    /// How the answer compared to the accepted answers
+    pub matched: Match,
+    /// How well the translation was recalled
+    pub grade: Grade,
+}
+
+pub struct Guess {
+    direction: Direction,
+    translation: Translation,
+    counterparts: Vec<String>,
+    choices: Vec<String>,
+    /// Which of the choices is the right one
+    right_choice: usize,
+    matcher: Matcher,
+    near_miss: NearMiss,
+    presented_at: Instant,
+    hints: usize,
+    /// The translation as it was before the last answer, so its grading can be overruled
+    before_last_attempt: Option<Translation>,
+    attempts: Vec<Attempt>,
 }
 
 impl Guess {
+    pub fn new(direction: Direction, translation: Translation) -> Guess {
+        Guess {
+            direction,
+            translation,
+            counterparts: Vec::new(),
+            choices: Vec::new(),
+            right_choice: 0,
+            matcher: Matcher::default(),
+            near_miss: NearMiss::Wrong,
+            presented_at: Instant::now(),
+            before_last_attempt: None,
+            hints: 0,
+            attempts: Vec::new(),
+        }
+    }
+
+    /// Other translations of the prompt that will also be accepted as correct, eg the other
+    /// foreign words for the same local word
+    pub fn with_counterparts(mut self, counterparts: Vec<String>) -> Guess {
+        self.counterparts = counterparts;
+        self
+    }
+
+    /// Options to choose the answer from rather than typing it, `right_choice` is the index of
+    /// the one that is right
+    pub fn with_choices(mut self, choices: Vec<String>, right_choice: usize) -> Guess {
+        self.choices = choices;
+        self.right_choice = right_choice;
+        self
+    }
+
+    /// The options to choose the answer from, empty if the answer is to be typed
+    pub fn choices(&self) -> &[String] {
+        &self.choices
+    }
+
+    /// How answers are compared to the accepted answers
+    pub fn with_matcher(mut self, matcher: Matcher) -> Guess {
+        self.matcher = matcher;
+        self
+    }
+
+    /// How to grade answers that are only a typo away from an accepted answer, by default they
+    /// are wrong
+    pub fn with_near_miss(mut self, near_miss: NearMiss) -> Guess {
+        self.near_miss = near_miss;
+        self
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
     pub fn render(&self) -> &str {
-        match self {
-            Guess::Local(translation) => translation.foreign.as_str(),
-            Guess::Foreign(translation) => translation.local.as_str(),
+        match self.direction {
+            Direction::Local => self.translation.foreign.as_str(),
+            Direction::Foreign => self.translation.local.as_str(),
         }
     }
 
     pub fn render_translation(&self) -> &str {
-        match self {
-            Guess::Local(translation) => translation.local.as_str(),
-            Guess::Foreign(translation) => translation.foreign.as_str(),
+        match self.direction {
+            Direction::Local => self.translation.local.as_str(),
+            Direction::Foreign => self.translation.foreign.as_str(),
         }
     }
 
+    /// Every answer that will be accepted, starting with the translation itself and its
+    /// alternates
+    pub fn accepted_answers(&self) -> Vec<&str> {
+        let mut answers = self.translation.answers(self.direction);
+        answers.extend(self.counterparts.iter().map(String::as_str));
+        answers
+    }
+
+    /// Reveals a little more of the answer each time it is asked for, first its length, then its
+    /// first letter and then another letter each time. Letters that haven't been revealed are
+    /// shown as `_`. Returns `None` once there is nothing left to reveal.
+    pub fn hint(&mut self) -> Option<String> {
+        let answer = variants(self.render_translation()).swap_remove(0);
+        let letters = answer.chars().filter(|c| c.is_alphanumeric()).count();
+        if self.hints > letters {
+            return None;
+        }
+        let mut revealed = 0;
+        let hint = answer
+            .split_whitespace()
+            .map(|word| {
+                word.chars()
+                    .map(|c| {
+                        if !c.is_alphanumeric() {
+                            c
+                        } else if revealed < self.hints {
+                            revealed += 1;
+                            c
+                        } else {
+                            '_'
+                        }
+                    })
+                    .map(String::from)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("   ");
+        self.hints += 1;
+        Some(hint)
+    }
+
+    /// How many hints have been given
+    pub fn hints(&self) -> usize {
+        self.hints
+    }
+
+    /// Grades an answer, returning true if it was correct. How a near miss was graded can be
+    /// seen in the last of the `attempts`.
     pub fn guess(&mut self, guess: &str) -> bool {
-        match self {
-            Guess::Local(ref mut translation) => translation.guess_local(guess),
-            Guess::Foreign(ref mut translation) => translation.guess_foreign(guess),
+        let matched = match self.direction {
+            Direction::Local => self.matcher.for_local(),
+            Direction::Foreign => self.matcher.clone(),
+        }
+        .check(guess, &self.accepted_answers());
+        let outcome = match matched {
+            Match::Exact | Match::Transliterated { .. } | Match::Diacritics { .. } => {
+                Outcome::Correct
+            }
+            Match::Close { .. } => self.near_miss.outcome(),
+            Match::Wrong => Outcome::Incorrect,
+        };
+        let outcome = match outcome {
+            Outcome::Correct if self.hints > 0 => Outcome::Hinted,
+            outcome => outcome,
+        };
+        let correct = outcome == Outcome::Correct;
+        self.record(guess, correct, outcome, matched, outcome.grade());
+        correct
+    }
+
+    /// Grades the choice at an index, returning true if it was the right one. Only the option
+    /// itself is right, however close the others are to it.
+    pub fn choose(&mut self, index: usize) -> bool {
+        let answer = self.choices.get(index).cloned().unwrap_or_default();
+        let (outcome, matched) = if index == self.right_choice {
+            (Outcome::Correct, Match::Exact)
+        } else {
+            (Outcome::Incorrect, Match::Wrong)
+        };
+        let outcome = match outcome {
+            Outcome::Correct if self.hints > 0 => Outcome::Hinted,
+            outcome => outcome,
+        };
+        let correct = outcome == Outcome::Correct;
+        self.record(&answer, correct, outcome, matched, outcome.grade());
+        correct
+    }
+
+    /// Grades a flashcard, where the user has been shown the answer and says how well they
+    /// remembered it, returning true if they did
+    pub fn grade(&mut self, grade: Grade) -> bool {
+        let correct = grade.is_correct();
+        let (outcome, matched) = if correct {
+            (Outcome::Correct, Match::Exact)
+        } else {
+            (Outcome::Incorrect, Match::Wrong)
+        };
+        self.record("", correct, outcome, matched, grade);
+        correct
+    }
+
+    fn record(
+        &mut self,
+        answer: &str,
+        correct: bool,
+        outcome: Outcome,
+        matched: Match,
+        grade: Grade,
+    ) {
+        self.before_last_attempt = Some(self.translation.clone());
+        self.translation.record_guess(self.direction, correct);
+        if outcome == Outcome::Hinted {
+            self.translation.record_hinted(self.direction);
+        }
+        let now = Utc::now().naive_utc();
+        // Answering a card before it is due, eg in endless mode, is practice and leaves the
+        // schedule alone, otherwise a few rounds would push it weeks out
+        if self.translation.is_due(now) {
+            self.translation.schedule(grade.quality(), now);
         }
+        self.translation.last_reviewed_at = Some(now);
+        self.attempts.push(Attempt {
+            reviewed_at: now,
+            direction: self.direction,
+            answer: answer.to_string(),
+            correct,
+            response_time: self.presented_at.elapsed(),
+            outcome,
+            matched,
+            grade,
+        });
+    }
+
+    /// Marks the last answer as correct after all, eg when it was an alternative the translation
+    /// doesn't have yet. The counts and schedule are put back as they were before the answer and
+    /// it is graded again as correct, or as hinted if there were hints. Returns false if there is
+    /// no answer waiting to be saved, or it was already correct.
+    pub fn overrule(&mut self) -> bool {
+        let attempt = match self.attempts.last() {
+            Some(attempt) if !attempt.correct && attempt.outcome != Outcome::Hinted => {
+                attempt.clone()
+            }
+            _ => return false,
+        };
+        let before = match self.before_last_attempt.take() {
+            Some(before) => before,
+            None => return false,
+        };
+        self.attempts.pop();
+        self.translation = before;
+        let outcome = if self.hints > 0 {
+            Outcome::Hinted
+        } else {
+            Outcome::Correct
+        };
+        let correct = outcome == Outcome::Correct;
+        self.record(
+            &attempt.answer,
+            correct,
+            outcome,
+            Match::Exact,
+            outcome.grade(),
+        );
+        true
+    }
+
+    /// Accepts another answer for the side of the translation being guessed, saved along with
+    /// the guess. Returns false if it was already accepted.
+    pub fn add_alternate(&mut self, alternate: &str) -> bool {
+        self.translation
+            .alternates_mut(self.direction)
+            .add(alternate)
+    }
+
+    /// Answers given since the guess was last saved
+    pub fn attempts(&self) -> &[Attempt] {
+        &self.attempts
+    }
+
+    pub(crate) fn take_attempts(&mut self) -> Vec<Attempt> {
+        std::mem::take(&mut self.attempts)
     }
 }
 
@@ -34,49 +337,49 @@ impl Deref for Guess {
     type Target = Translation;
 
     fn deref(&self) -> &Self::Target {
-        match self {
-            Guess::Local(translation) => translation,
-            Guess::Foreign(translation) => translation,
-        }
+        &self.translation
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Guess, Translation};
+    use chrono::{Duration, Utc};
+
+    use super::{Direction, Guess, Translation};
+    use crate::{Grade, Match, NearMiss, Outcome};
 
     #[test]
     fn test_render_local() {
         let translation = Translation::new("yes", "はい");
-        let guess = Guess::Local(translation);
+        let guess = Guess::new(Direction::Local, translation);
         assert_eq!(guess.render(), "はい");
     }
 
     #[test]
     fn test_render_foreign() {
         let translation = Translation::new("yes", "はい");
-        let guess = Guess::Foreign(translation);
+        let guess = Guess::new(Direction::Foreign, translation);
         assert_eq!(guess.render(), "yes");
     }
 
     #[test]
     fn test_render_translation_local() {
         let translation = Translation::new("yes", "はい");
-        let guess = Guess::Local(translation);
+        let guess = Guess::new(Direction::Local, translation);
         assert_eq!(guess.render_translation(), "yes");
     }
 
     #[test]
     fn test_render_translation_foreign() {
         let translation = Translation::new("yes", "はい");
-        let guess = Guess::Foreign(translation);
+        let guess = Guess::new(Direction::Foreign, translation);
         assert_eq!(guess.render_translation(), "はい");
     }
 
     #[test]
     fn test_guess_local() {
         let translation = Translation::new("yes", "はい");
-        let mut guess = Guess::Local(translation);
+        let mut guess = Guess::new(Direction::Local, translation);
         assert!(guess.guess("yes"));
         assert_eq!(guess.guesses_local_total, 1);
         assert_eq!(guess.guesses_foreign_total, 0);
@@ -85,9 +388,173 @@ mod tests {
     #[test]
     fn test_guess_foreign() {
         let translation = Translation::new("yes", "はい");
-        let mut guess = Guess::Foreign(translation);
+        let mut guess = Guess::new(Direction::Foreign, translation);
         assert!(guess.guess("はい"));
         assert_eq!(guess.guesses_foreign_total, 1);
         assert_eq!(guess.guesses_local_total, 0);
     }
+
+    #[test]
+    fn test_guess_records_attempts() {
+        let translation = Translation::new("yes", "はい");
+        let mut guess = Guess::new(Direction::Foreign, translation);
+        assert!(!guess.guess("いいえ"));
+        assert!(guess.guess("はい"));
+
+        let attempts = guess.attempts();
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].answer, "いいえ");
+        assert!(!attempts[0].correct);
+        assert_eq!(attempts[0].direction, Direction::Foreign);
+        assert_eq!(attempts[1].answer, "はい");
+        assert!(attempts[1].correct);
+    }
+
+    #[test]
+    fn test_near_misses() {
+        let translation = Translation::new("morning", "おはよう");
+        let mut guess = Guess::new(Direction::Local, translation.clone());
+        assert!(!guess.guess("mornign"));
+        assert_eq!(guess.attempts()[0].outcome, Outcome::Incorrect);
+        assert_eq!(
+            guess.attempts()[0].matched,
+            Match::Close {
+                expected: "morning".to_string()
+            }
+        );
+
+        let mut guess =
+            Guess::new(Direction::Local, translation.clone()).with_near_miss(NearMiss::Correct);
+        assert!(guess.guess("mornign"));
+        assert_eq!(guess.guesses_local_correct, 1);
+
+        let mut guess = Guess::new(Direction::Local, translation).with_near_miss(NearMiss::Typo);
+        assert!(!guess.guess("mornign"));
+        assert_eq!(guess.attempts()[0].outcome, Outcome::Typo);
+        assert_eq!(guess.guesses_local_correct, 0);
+        assert_eq!(guess.repetitions, 1);
+        assert_eq!(guess.lapses, 0);
+    }
+
+    #[test]
+    fn test_grade() {
+        let translation = Translation::new("good morning", "おはようございます");
+        let mut guess = Guess::new(Direction::Foreign, translation.clone());
+        assert!(guess.grade(Grade::Easy));
+        assert_eq!(guess.attempts()[0].grade, Grade::Easy);
+        assert_eq!(guess.attempts()[0].outcome, Outcome::Correct);
+        assert_eq!(guess.guesses_foreign_correct, 1);
+
+        let mut guess = Guess::new(Direction::Foreign, translation);
+        assert!(!guess.grade(Grade::Again));
+        assert_eq!(guess.attempts()[0].outcome, Outcome::Incorrect);
+        assert_eq!(guess.lapses, 1);
+
+        let mut guess = Guess::new(Direction::Local, Translation::new("yes", "はい"));
+        guess.guess("yse");
+        assert_eq!(guess.attempts()[0].grade, Grade::Again);
+    }
+
+    #[test]
+    fn test_practice_before_due_keeps_schedule() {
+        let mut translation = Translation::new("yes", "はい");
+        translation.due_at = Some(Utc::now().naive_utc() + Duration::days(3));
+        translation.interval_days = 6;
+        translation.repetitions = 2;
+        let mut guess = Guess::new(Direction::Local, translation.clone());
+        assert!(guess.guess("yes"));
+        assert!(!guess.guess("no"));
+        assert_eq!(guess.interval_days, 6);
+        assert_eq!(guess.repetitions, 2);
+        assert_eq!(guess.lapses, 0);
+        assert_eq!(guess.due_at, translation.due_at);
+        assert_eq!(guess.guesses_local_total, 2);
+        assert_eq!(guess.guesses_local_correct, 1);
+        assert_eq!(guess.attempts().len(), 2);
+        assert!(guess.last_reviewed_at.is_some());
+    }
+
+    #[test]
+    fn test_overrule() {
+        let translation = Translation::new("colour", "couleur");
+        let mut guess = Guess::new(Direction::Local, translation);
+        assert!(!guess.overrule());
+        assert!(!guess.guess("hue"));
+        assert_eq!(guess.lapses, 1);
+        assert!(guess.overrule());
+        assert!(!guess.overrule());
+        assert_eq!(guess.attempts().len(), 1);
+        assert_eq!(guess.attempts()[0].answer, "hue");
+        assert_eq!(guess.attempts()[0].outcome, Outcome::Correct);
+        assert_eq!(guess.attempts()[0].grade, Grade::Good);
+        assert_eq!(guess.guesses_local_total, 1);
+        assert_eq!(guess.guesses_local_correct, 1);
+        assert_eq!(guess.lapses, 0);
+        assert_eq!(guess.repetitions, 1);
+
+        assert!(guess.add_alternate("hue"));
+        assert!(!guess.add_alternate("Hue"));
+        assert!(guess.guess("hue"));
+    }
+
+    #[test]
+    fn test_hints() {
+        let translation = Translation::new("(to) eat", "manger");
+        let mut guess = Guess::new(Direction::Local, translation);
+        assert_eq!(guess.hint().as_deref(), Some("_ _   _ _ _"));
+        assert_eq!(guess.hint().as_deref(), Some("t _   _ _ _"));
+        assert_eq!(guess.hint().as_deref(), Some("t o   _ _ _"));
+        assert_eq!(guess.hint().as_deref(), Some("t o   e _ _"));
+        assert_eq!(guess.hint().as_deref(), Some("t o   e a _"));
+        assert_eq!(guess.hint().as_deref(), Some("t o   e a t"));
+        assert_eq!(guess.hint(), None);
+        assert_eq!(guess.hints(), 6);
+
+        assert!(!guess.guess("to eat"));
+        assert_eq!(guess.attempts()[0].outcome, Outcome::Hinted);
+        assert_eq!(guess.guesses_local_total, 1);
+        assert_eq!(guess.guesses_local_correct, 0);
+        assert_eq!(guess.guesses_local_hinted, 1);
+        assert_eq!(guess.repetitions, 1);
+        assert_eq!(guess.lapses, 0);
+    }
+
+    #[test]
+    fn test_choose() {
+        let translation = Translation::new("reason", "raison");
+        let choices = vec!["maison".to_string(), "raison".to_string()];
+        // The wrong option is a near miss, which the deck counts as correct when it's typed
+        let new_guess = || {
+            Guess::new(Direction::Foreign, translation.clone())
+                .with_choices(choices.clone(), 1)
+                .with_near_miss(NearMiss::Correct)
+        };
+
+        let mut guess = new_guess();
+        assert!(!guess.choose(0));
+        assert_eq!(guess.attempts()[0].outcome, Outcome::Incorrect);
+        assert_eq!(guess.attempts()[0].matched, Match::Wrong);
+        assert_eq!(guess.attempts()[0].answer, "maison");
+        assert_eq!(guess.guesses_foreign_correct, 0);
+
+        let mut guess = new_guess();
+        assert!(guess.choose(1));
+        assert_eq!(guess.attempts()[0].outcome, Outcome::Correct);
+        assert_eq!(guess.attempts()[0].matched, Match::Exact);
+
+        let mut guess = new_guess();
+        assert!(guess.guess("maison"));
+    }
+
+    #[test]
+    fn test_guess_accepts_counterparts() {
+        let translation = Translation::new("hello", "こんにちは");
+        let mut guess = Guess::new(Direction::Foreign, translation)
+            .with_counterparts(vec!["もしもし".to_string()]);
+        assert_eq!(guess.accepted_answers(), vec!["こんにちは", "もしもし"]);
+        assert!(guess.guess("もしもし"));
+        assert!(!guess.guess("はい"));
+        assert_eq!(guess.guesses_foreign_total, 2);
+        assert_eq!(guess.guesses_foreign_correct, 1);
+    }
 }
@@ -1,67 +1,447 @@
-use diesel::{QueryDsl, RunQueryDsl, SqliteConnection};
+use chrono::Utc;
+use diesel::{
+    BoolExpressionMethods, ExpressionMethods, QueryDsl, QueryResult, RunQueryDsl, SqliteConnection,
+};
+use rand::seq::SliceRandom;
+use rand::Rng;
 
-use crate::{schema::RANDOM, Guess, Translation, VocabStoreError};
+use crate::schema::{decks, translation_tags, translations};
+use crate::vocab_store::answer::same_word;
+use crate::vocab_store::deck_translations;
+use crate::vocab_store::syntax::variants;
+use crate::{Deck, Direction, Guess, SelectionStrategy, Translation, VocabStoreError};
 
 pub struct Guesses<'a> {
     conn: &'a SqliteConnection,
+    deck: i32,
+    strategy: Box<dyn SelectionStrategy + 'a>,
+    tag: Option<&'a str>,
+    choices: usize,
+    /// Every translation in the deck, for the answers and options that come from other
+    /// translations. Loaded with the first guess rather than for every one.
+    deck_translations: Option<Vec<Translation>>,
 }
 
 impl<'a> Guesses<'a> {
-    pub fn new(conn: &'a SqliteConnection) -> Guesses<'a> {
-        Guesses { conn }
+    pub fn new(
+        conn: &'a SqliteConnection,
+        deck: i32,
+        strategy: Box<dyn SelectionStrategy + 'a>,
+    ) -> Guesses<'a> {
+        Guesses {
+            conn,
+            deck,
+            strategy,
+            tag: None,
+            choices: 0,
+            deck_translations: None,
+        }
+    }
+
+    /// Only guess translations with the given tag
+    pub fn with_tag(mut self, tag: Option<&'a str>) -> Guesses<'a> {
+        self.tag = tag;
+        self
+    }
+
+    /// Give each guess this many options to choose the answer from, see `Guess::choices`. Fewer
+    /// than two means the answer is typed.
+    pub fn with_choices(mut self, choices: usize) -> Guesses<'a> {
+        self.choices = choices;
+        self
+    }
+}
+
+/// The answers of other translations that share the same prompt, in any case or normal form
+fn counterparts(
+    translation: &Translation,
+    direction: Direction,
+    others: &[Translation],
+) -> Vec<String> {
+    others
+        .iter()
+        .filter(|other| other.id != translation.id)
+        .filter(|other| match direction {
+            Direction::Local => same_word(&other.foreign, &translation.foreign),
+            Direction::Foreign => same_word(&other.local, &translation.local),
+        })
+        .flat_map(|other| other.answers(direction))
+        .map(str::to_string)
+        .collect()
+}
+
+/// The writing system a word is in, going by its first letter
+#[derive(PartialEq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Kana,
+    Han,
+    Hangul,
+    Other,
+}
+
+fn script(word: &str) -> Script {
+    match word.chars().find(|c| c.is_alphabetic()) {
+        Some('\u{0370}'..='\u{03FF}') => Script::Greek,
+        Some('\u{0400}'..='\u{04FF}') => Script::Cyrillic,
+        Some('\u{3040}'..='\u{30FF}') => Script::Kana,
+        Some('\u{4E00}'..='\u{9FFF}') => Script::Han,
+        Some('\u{AC00}'..='\u{D7A3}') => Script::Hangul,
+        Some(c) if c.is_ascii_alphabetic() || ('\u{00C0}'..='\u{024F}').contains(&c) => {
+            Script::Latin
+        }
+        _ => Script::Other,
+    }
+}
+
+/// The ids of the translations that share a tag with the given one
+fn tagged_alike(conn: &SqliteConnection, translation: &Translation) -> QueryResult<Vec<i32>> {
+    let tags = translation_tags::table
+        .filter(translation_tags::translation_id.eq(translation.id))
+        .select(translation_tags::tag_id)
+        .load::<i32>(conn)?;
+    translation_tags::table
+        .filter(translation_tags::tag_id.eq_any(tags))
+        .select(translation_tags::translation_id)
+        .load(conn)
+}
+
+/// The answer as an option to choose from, with any optional parts included
+fn as_option(word: &str) -> String {
+    variants(word).swap_remove(0)
+}
+
+/// Wrong answers to choose from alongside the right one. Answers that share a tag with the
+/// translation, are in the same script and are about as long are preferred, so the right answer
+/// doesn't stand out.
+fn distractors(
+    translation: &Translation,
+    direction: Direction,
+    others: &[Translation],
+    tagged_alike: &[i32],
+    count: usize,
+    accepted: &[&str],
+) -> Vec<String> {
+    let answer = as_option(match direction {
+        Direction::Local => &translation.local,
+        Direction::Foreign => &translation.foreign,
+    });
+    let length = answer.chars().count();
+    let mut candidates: Vec<(bool, String)> = others
+        .iter()
+        .filter(|other| other.id != translation.id)
+        .map(|other| {
+            let word = match direction {
+                Direction::Local => &other.local,
+                Direction::Foreign => &other.foreign,
+            };
+            (tagged_alike.contains(&other.id), as_option(word))
+        })
+        .filter(|(_, option)| {
+            !accepted
+                .iter()
+                .any(|a| variants(a).iter().any(|v| same_word(v, option)))
+        })
+        .collect();
+    candidates.shuffle(&mut rand::thread_rng());
+    candidates.sort_by_key(|(tagged, option)| {
+        (
+            !tagged,
+            script(option) != script(&answer),
+            (option.chars().count() as isize - length as isize).abs(),
+        )
+    });
+    let mut chosen: Vec<String> = Vec::new();
+    for (_, option) in candidates {
+        if chosen.len() == count {
+            break;
+        }
+        if !chosen.iter().any(|c| same_word(c, &option)) {
+            chosen.push(option);
+        }
     }
+    chosen
 }
 
 impl<'a> Iterator for Guesses<'a> {
     type Item = Result<Guess, VocabStoreError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        use crate::schema::translations::dsl::*;
-
-        let query_result = translations
-            .order(RANDOM)
-            .limit(1)
-            .load::<Translation>(self.conn);
-
-        match query_result {
-            Err(e) => Some(Err(e.into())),
-            Ok(mut translation_results) => translation_results.pop().map(|t: Translation| {
-                Ok(if t.guesses_foreign_correct > t.guesses_local_correct {
-                    Guess::Local(t)
-                } else {
-                    Guess::Foreign(t)
-                })
-            }),
+        let deck = match decks::table.find(self.deck).first::<Deck>(self.conn) {
+            Err(e) => return Some(Err(e.into())),
+            Ok(deck) => deck,
+        };
+        let mut query = deck_translations(self.deck, self.tag);
+        if self.strategy.due_only() {
+            query = query.filter(
+                translations::due_at
+                    .is_null()
+                    .or(translations::due_at.le(Utc::now().naive_utc())),
+            );
         }
+        let mut candidates = match query.load::<Translation>(self.conn) {
+            Err(e) => return Some(Err(e.into())),
+            Ok(candidates) => candidates,
+        };
+
+        let index = self
+            .strategy
+            .select(&candidates)
+            .filter(|&index| index < candidates.len())?;
+        Some(self.guess(candidates.swap_remove(index), &deck))
+    }
+}
+
+impl<'a> Guesses<'a> {
+    fn guess(&mut self, t: Translation, deck: &Deck) -> Result<Guess, VocabStoreError> {
+        // Answers from translations outside the tag are still accepted, so the whole deck is
+        // loaded once a translation has been selected
+        if self.deck_translations.is_none() {
+            self.deck_translations = Some(deck_translations(self.deck, None).load(self.conn)?);
+        }
+        let all = self
+            .deck_translations
+            .as_mut()
+            .expect("the deck's translations were just loaded");
+        // The selected translation has just been loaded, so it's the most up to date copy
+        if let Some(stale) = all.iter_mut().find(|other| other.id == t.id) {
+            *stale = t.clone();
+        }
+        let direction = if t.guesses_foreign_correct > t.guesses_local_correct {
+            Direction::Local
+        } else {
+            Direction::Foreign
+        };
+        let counterparts = counterparts(&t, direction, all);
+        let mut guess = Guess::new(direction, t)
+            .with_counterparts(counterparts)
+            .with_matcher(deck.matcher())
+            .with_near_miss(deck.near_miss);
+        if self.choices > 1 {
+            let tagged_alike = tagged_alike(self.conn, &guess)?;
+            let mut choices = distractors(
+                &guess,
+                direction,
+                all,
+                &tagged_alike,
+                self.choices - 1,
+                &guess.accepted_answers(),
+            );
+            let mut rng = rand::thread_rng();
+            choices.shuffle(&mut rng);
+            let right_choice = rng.gen_range(0..=choices.len());
+            choices.insert(right_choice, as_option(guess.render_translation()));
+            guess = guess.with_choices(choices, right_choice);
+        }
+        Ok(guess)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::cell::Cell;
     use std::fs;
     use std::ops::Deref;
+    use std::rc::Rc;
 
+    use chrono::{Duration, Utc};
     use diesel::{Connection, RunQueryDsl, SqliteConnection};
 
-    use crate::vocab_store::guesses::Guesses;
-    use crate::{Translation, VocabStore};
+    use crate::vocab_store::guesses::{counterparts, Guesses};
+    use crate::vocab_store::translation::NewTranslation;
+    use crate::{
+        Direction, DueForReview, SelectionStrategy, Translation, UniformRandom, VocabStore,
+    };
 
     const TEST_FILE: &str = "test.sqlite";
 
     #[test]
     fn test_gets_a_translation() {
-        let _ = fs::remove_file(&TEST_FILE); // Ok if it fails;
-        let _ = VocabStore::init(&TEST_FILE).unwrap();
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let _ = VocabStore::init(TEST_FILE).unwrap();
 
-        let conn = SqliteConnection::establish(&TEST_FILE).unwrap();
-        let mut guesses = Guesses::new(&conn);
-        let translation = Translation::new("yes", "はい");
+        let conn = SqliteConnection::establish(TEST_FILE).unwrap();
+        let mut guesses = Guesses::new(&conn, 1, Box::new(UniformRandom));
+        let mut translation = Translation::new("yes", "はい");
+        translation.deck_id = 1;
         diesel::insert_into(crate::schema::translations::table)
-            .values(&translation)
+            .values(NewTranslation::from(&translation))
             .execute(&conn)
             .unwrap();
+        translation.id = 1;
 
         assert_eq!(guesses.next().unwrap().unwrap().deref(), &translation);
         assert_eq!(guesses.next().unwrap().unwrap().deref(), &translation);
     }
+
+    #[test]
+    fn test_stops_when_strategy_selects_nothing() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        vocab_store.add(&Translation::new("yes", "はい")).unwrap();
+        vocab_store.add(&Translation::new("no", "いいえ")).unwrap();
+
+        let mut reviewed = vec![];
+        for guess in vocab_store.guesses(DueForReview) {
+            let mut guess = guess.unwrap();
+            let answer = guess.render_translation().to_string();
+            guess.guess(&answer);
+            vocab_store.save_guess(&mut guess).unwrap();
+            reviewed.push(guess.local.clone());
+        }
+        reviewed.sort();
+
+        assert_eq!(reviewed, vec!["no", "yes"]);
+        assert!(vocab_store.next_due().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_uses_strategy() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        vocab_store.add(&Translation::new("yes", "はい")).unwrap();
+        let mut overdue = Translation::new("no", "いいえ");
+        overdue.due_at = Some(Utc::now().naive_utc() - Duration::days(2));
+        vocab_store.add(&overdue).unwrap();
+
+        let guess = vocab_store.guesses(DueForReview).next().unwrap().unwrap();
+        assert_eq!(guess.local, "no");
+    }
+
+    #[test]
+    fn test_only_loads_due_candidates() {
+        struct Candidates(Rc<Cell<usize>>);
+
+        impl SelectionStrategy for Candidates {
+            fn select(&mut self, candidates: &[Translation]) -> Option<usize> {
+                self.0.set(candidates.len());
+                None
+            }
+
+            fn due_only(&self) -> bool {
+                true
+            }
+        }
+
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        vocab_store.add(&Translation::new("yes", "はい")).unwrap();
+        let mut later = Translation::new("no", "いいえ");
+        later.due_at = Some(Utc::now().naive_utc() + Duration::days(2));
+        vocab_store.add(&later).unwrap();
+        let mut overdue = Translation::new("cat", "猫");
+        overdue.due_at = Some(Utc::now().naive_utc() - Duration::days(2));
+        vocab_store.add(&overdue).unwrap();
+
+        let seen = Rc::new(Cell::new(0));
+        assert!(vocab_store
+            .guesses(Candidates(seen.clone()))
+            .next()
+            .is_none());
+        assert_eq!(seen.get(), 2);
+    }
+
+    #[test]
+    fn test_includes_counterparts() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        vocab_store
+            .add(&Translation::new("hello", "こんにちは"))
+            .unwrap();
+        vocab_store
+            .add(&Translation::new("hello", "もしもし"))
+            .unwrap();
+        vocab_store.add(&Translation::new("yes", "はい")).unwrap();
+
+        for guess in vocab_store.guesses(UniformRandom).take(10) {
+            let guess = guess.unwrap();
+            let mut expected = match guess.local.as_str() {
+                "hello" => vec!["こんにちは", "もしもし"],
+                _ => vec!["はい"],
+            };
+            let mut accepted = guess.accepted_answers();
+            accepted.sort();
+            expected.sort();
+            assert_eq!(accepted, expected);
+        }
+    }
+
+    #[test]
+    fn test_counterparts_ignore_case() {
+        let mut hello = Translation::new("Hello", "こんにちは");
+        hello.id = 1;
+        let mut other = Translation::new("hello", "もしもし");
+        other.id = 2;
+        let mut accented = Translation::new("cafe\u{301}", "喫茶店");
+        accented.id = 3;
+        let mut composed = Translation::new("Caf\u{e9}", "カフェ");
+        composed.id = 4;
+        let all = vec![hello.clone(), other, accented.clone(), composed];
+
+        assert_eq!(
+            counterparts(&hello, Direction::Foreign, &all),
+            vec!["もしもし"]
+        );
+        assert_eq!(
+            counterparts(&accented, Direction::Foreign, &all),
+            vec!["カフェ"]
+        );
+        assert!(counterparts(&hello, Direction::Local, &all).is_empty());
+    }
+
+    #[test]
+    fn test_choices() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        for (local, foreign) in &[
+            ("yes", "はい"),
+            ("no", "いいえ"),
+            ("cat", "猫"),
+            ("dog", "犬"),
+        ] {
+            let translation = Translation::new(local, foreign);
+            vocab_store.add(&translation).unwrap();
+            if *local == "yes" || *local == "no" {
+                let translation = vocab_store.find(local, foreign).unwrap().unwrap();
+                vocab_store.add_tag(&translation, "answers").unwrap();
+            }
+        }
+
+        for guess in vocab_store
+            .guesses(UniformRandom)
+            .with_tag(Some("answers"))
+            .with_choices(2)
+            .take(10)
+        {
+            let mut guess = guess.unwrap();
+            let mut choices = guess.choices().to_vec();
+            choices.sort();
+            let mut expected = match guess.direction() {
+                Direction::Local => vec!["no", "yes"],
+                Direction::Foreign => vec!["いいえ", "はい"],
+            };
+            expected.sort();
+            assert_eq!(choices, expected);
+            let right = guess
+                .choices()
+                .iter()
+                .position(|choice| choice == guess.render_translation())
+                .unwrap();
+            assert!(guess.choose(right));
+        }
+
+        let guess = vocab_store
+            .guesses(UniformRandom)
+            .with_choices(10)
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(guess.choices().len(), 4);
+        assert!(guess
+            .choices()
+            .iter()
+            .any(|choice| choice == guess.render_translation()));
+    }
 }
@@ -0,0 +1,136 @@
+use std::convert::Infallible;
+use std::fmt;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use diesel::sqlite::Sqlite;
+
+/// What sets one kind of list apart, see `Alternates` and `Examples`
+pub trait ListKind {
+    /// Separates the entries when the list is stored (and exported) as a single string
+    const SEPARATOR: char;
+    /// Put in place of the separator if an entry contains it
+    const REPLACEMENT: &'static str;
+
+    /// Whether two entries are the same, so only one of them is kept
+    fn same(a: &str, b: &str) -> bool;
+}
+
+/// A list of distinct, non-empty entries stored in a single column
+#[derive(AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub struct List<K: ListKind> {
+    entries: Vec<String>,
+    kind: PhantomData<K>,
+}
+
+// Implemented by hand as deriving them would require the kind to implement them too
+impl<K: ListKind> Default for List<K> {
+    fn default() -> Self {
+        List {
+            entries: Vec::new(),
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<K: ListKind> Clone for List<K> {
+    fn clone(&self) -> Self {
+        List {
+            entries: self.entries.clone(),
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<K: ListKind> PartialEq for List<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<K: ListKind> fmt::Debug for List<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("List").field(&self.entries).finish()
+    }
+}
+
+impl<K: ListKind> List<K> {
+    pub fn new<S: AsRef<str>>(entries: &[S]) -> List<K> {
+        let mut new = List::default();
+        for entry in entries {
+            new.add(entry.as_ref());
+        }
+        new
+    }
+
+    /// Adds an entry, returning false if it was empty or already there
+    pub fn add(&mut self, entry: &str) -> bool {
+        let entry = entry
+            .replace(K::SEPARATOR, K::REPLACEMENT)
+            .trim()
+            .to_string();
+        if entry.is_empty() || self.entries.iter().any(|e| K::same(e, &entry)) {
+            return false;
+        }
+        self.entries.push(entry);
+        true
+    }
+
+    /// Removes an entry, returning false if it wasn't there
+    pub fn remove(&mut self, entry: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| !K::same(e, entry));
+        self.entries.len() != before
+    }
+
+    /// Every entry in either list, without duplicates
+    pub fn union(&self, other: &List<K>) -> List<K> {
+        let mut union = self.clone();
+        for entry in other.iter() {
+            union.add(entry);
+        }
+        union
+    }
+}
+
+impl<K: ListKind> Deref for List<K> {
+    type Target = Vec<String>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entries
+    }
+}
+
+impl<K: ListKind> fmt::Display for List<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.entries.join(&K::SEPARATOR.to_string()))
+    }
+}
+
+impl<K: ListKind> FromStr for List<K> {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(List::new(&s.split(K::SEPARATOR).collect::<Vec<_>>()))
+    }
+}
+
+impl<K: ListKind> ToSql<Text, Sqlite> for List<K> {
+    fn to_sql<W: Write>(&self, out: &mut Output<W, Sqlite>) -> serialize::Result {
+        <String as ToSql<Text, Sqlite>>::to_sql(&self.to_string(), out)
+    }
+}
+
+impl<K: ListKind> FromSql<Text, Sqlite> for List<K> {
+    fn from_sql(bytes: Option<&<Sqlite as Backend>::RawValue>) -> deserialize::Result<Self> {
+        let s = <String as FromSql<Text, Sqlite>>::from_sql(bytes)?;
+        Ok(s.parse().unwrap_or_default())
+    }
+}
@@ -0,0 +1,43 @@
+use std::path::{Path, PathBuf};
+
+/// The name of the database file vocab looks for
+pub const DATABASE_FILE: &str = "vocab.sqlite";
+
+/// Looks for a database in the directory and then each of its parents in turn, the same way git
+/// finds `.git`
+pub fn find_database(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(DATABASE_FILE))
+        .find(|file| file.is_file())
+}
+
+/// Where the database is kept when it isn't in the current directory or any of its parents, eg
+/// `~/.local/share/vocab/vocab.sqlite`
+pub fn global_database() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("vocab").join(DATABASE_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::{find_database, DATABASE_FILE};
+
+    #[test]
+    fn test_find_database_in_parent() {
+        let test_dir = env::temp_dir().join("vocab_test_location");
+        let _ = fs::remove_dir_all(&test_dir); // Ok if it fails;
+        let nested = test_dir.join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+        let database = test_dir.join(DATABASE_FILE);
+
+        assert_eq!(find_database(&nested), None);
+        fs::write(&database, "").unwrap();
+        assert_eq!(find_database(&nested), Some(database.clone()));
+        assert_eq!(find_database(&test_dir), Some(database));
+
+        fs::remove_dir_all(&test_dir).unwrap();
+    }
+}
@@ -0,0 +1,405 @@
+use std::fs::{File, OpenOptions};
+use std::io;
+
+use diesel::connection::SimpleConnection;
+use diesel::sql_types::Text;
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+
+use crate::VocabStoreError;
+
+const METADATA: &str = "CREATE TABLE IF NOT EXISTS metadata
+(
+    \"key\"   VARCHAR NOT NULL PRIMARY KEY,
+    \"value\" VARCHAR NOT NULL
+);";
+
+const SCHEMA_VERSION: &str = "schema_version";
+
+/// A single, ordered change to the database schema
+struct Migration {
+    version: i32,
+    name: &'static str,
+    sql: &'static str,
+}
+
+/// Every migration in the order it must be applied. New migrations must only ever be appended.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "vocab_table",
+        sql: include_str!("../migrations/2020-02-22_vocab_table.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "reviews",
+        sql: include_str!("../migrations/2026-10-18_reviews.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "schedule",
+        sql: include_str!("../migrations/2026-10-18_schedule.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "last_reviewed",
+        sql: include_str!("../migrations/2026-10-18_last_reviewed.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "many_to_many",
+        sql: include_str!("../migrations/2026-10-18_many_to_many.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "alternates",
+        sql: include_str!("../migrations/2026-10-18_alternates.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "tags",
+        sql: include_str!("../migrations/2026-10-18_tags.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "decks",
+        sql: include_str!("../migrations/2026-10-18_decks.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "uuids",
+        sql: include_str!("../migrations/2026-10-18_uuids.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "notes",
+        sql: include_str!("../migrations/2026-10-18_notes.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "readings",
+        sql: include_str!("../migrations/2026-10-18_readings.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "grammar",
+        sql: include_str!("../migrations/2026-10-18_grammar.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "near_misses",
+        sql: include_str!("../migrations/2026-10-18_near_misses.sql"),
+    },
+    Migration {
+        version: 14,
+        name: "ignore_diacritics",
+        sql: include_str!("../migrations/2026-10-18_ignore_diacritics.sql"),
+    },
+    Migration {
+        version: 15,
+        name: "case",
+        sql: include_str!("../migrations/2026-10-18_case.sql"),
+    },
+    Migration {
+        version: 16,
+        name: "transliteration",
+        sql: include_str!("../migrations/2026-10-18_transliteration.sql"),
+    },
+    Migration {
+        version: 17,
+        name: "grades",
+        sql: include_str!("../migrations/2026-10-18_grades.sql"),
+    },
+    Migration {
+        version: 18,
+        name: "hints",
+        sql: include_str!("../migrations/2026-10-18_hints.sql"),
+    },
+    Migration {
+        version: 19,
+        name: "overrule",
+        sql: include_str!("../migrations/2026-10-18_overrule.sql"),
+    },
+];
+
+/// Where a database is in relation to the migrations this binary knows about
+#[derive(Debug, PartialEq)]
+pub struct MigrationStatus {
+    pub current: i32,
+    pub latest: i32,
+    pub pending: Vec<&'static str>,
+}
+
+impl MigrationStatus {
+    pub fn is_up_to_date(&self) -> bool {
+        self.current == self.latest
+    }
+
+    pub fn is_too_new(&self) -> bool {
+        self.current > self.latest
+    }
+
+    /// Whether upgrading will back up the database first, which it does unless the database is
+    /// new and there is nothing to lose
+    pub fn needs_backup(&self) -> bool {
+        !self.pending.is_empty() && self.current > 0
+    }
+}
+
+#[derive(QueryableByName)]
+struct TableName {
+    #[sql_type = "Text"]
+    #[allow(dead_code)]
+    name: String,
+}
+
+pub fn latest_version() -> i32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+fn table_exists(conn: &SqliteConnection, table: &str) -> Result<bool, VocabStoreError> {
+    let tables =
+        diesel::sql_query("SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?")
+            .bind::<Text, _>(table)
+            .load::<TableName>(conn)?;
+    Ok(!tables.is_empty())
+}
+
+/// Finds the schema version of the database. Databases created before versioning was introduced
+/// have no metadata table, but if they have a translations table they are at version 1.
+pub fn current_version(conn: &SqliteConnection) -> Result<i32, VocabStoreError> {
+    use crate::schema::metadata::dsl::*;
+
+    if table_exists(conn, "metadata")? {
+        let version = metadata
+            .select(value)
+            .filter(key.eq(SCHEMA_VERSION))
+            .first::<String>(conn)
+            .ok();
+        if let Some(version) = version {
+            return version
+                .parse()
+                .map_err(|e| VocabStoreError::UnexpectedError(Box::new(e)));
+        }
+    }
+
+    if table_exists(conn, "translations")? {
+        Ok(1)
+    } else {
+        Ok(0)
+    }
+}
+
+fn set_version(conn: &SqliteConnection, version: i32) -> Result<(), VocabStoreError> {
+    use crate::schema::metadata::dsl::*;
+
+    diesel::replace_into(metadata)
+        .values((key.eq(SCHEMA_VERSION), value.eq(version.to_string())))
+        .execute(conn)?;
+    Ok(())
+}
+
+pub fn status(conn: &SqliteConnection) -> Result<MigrationStatus, VocabStoreError> {
+    let current = current_version(conn)?;
+    Ok(MigrationStatus {
+        current,
+        latest: latest_version(),
+        pending: MIGRATIONS
+            .iter()
+            .filter(|m| m.version > current)
+            .map(|m| m.name)
+            .collect(),
+    })
+}
+
+/// Copies the database file aside before it is changed, returning where the copy was put. An
+/// earlier backup of the same version is never overwritten, the new copy is numbered instead, eg
+/// `vocab.sqlite.v3.1.bak`.
+pub fn backup(file: &str, version: i32) -> Result<String, VocabStoreError> {
+    for copy in 0.. {
+        let backup_file = match copy {
+            0 => format!("{}.v{}.bak", file, version),
+            _ => format!("{}.v{}.{}.bak", file, version, copy),
+        };
+        let mut backup = match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&backup_file)
+        {
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            result => result?,
+        };
+        io::copy(&mut File::open(file)?, &mut backup)?;
+        return Ok(backup_file);
+    }
+    unreachable!("there is always another number to try")
+}
+
+/// Applies every migration newer than the database's current version, each in its own
+/// transaction so a failure leaves the database at the last version that succeeded.
+///
+/// Foreign keys are switched off while migrating so tables can be rebuilt without cascading
+/// deletes into the tables that reference them, and switched on again afterwards, even when
+/// migrating fails.
+pub fn migrate(conn: &SqliteConnection) -> Result<MigrationStatus, VocabStoreError> {
+    conn.batch_execute("PRAGMA foreign_keys = OFF")?;
+    let migrated = apply_migrations(conn);
+    conn.batch_execute("PRAGMA foreign_keys = ON")?;
+    migrated?;
+
+    status(conn)
+}
+
+fn apply_migrations(conn: &SqliteConnection) -> Result<(), VocabStoreError> {
+    conn.batch_execute(METADATA)?;
+    let current = current_version(conn)?;
+    if current > latest_version() {
+        return Err(VocabStoreError::SchemaTooNew {
+            found: current,
+            supported: latest_version(),
+        });
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        conn.transaction::<_, VocabStoreError, _>(|| {
+            conn.batch_execute(migration.sql)?;
+            set_version(conn, migration.version)?;
+            Ok(())
+        })?;
+    }
+    set_version(conn, current.max(latest_version()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use diesel::connection::SimpleConnection;
+    use diesel::{Connection, SqliteConnection};
+
+    use diesel::sql_types::Integer;
+    use diesel::RunQueryDsl;
+
+    use super::{backup, current_version, latest_version, migrate, set_version, status};
+    use crate::{VocabStore, VocabStoreError};
+
+    #[derive(QueryableByName)]
+    struct ForeignKeys {
+        #[sql_type = "Integer"]
+        foreign_keys: i32,
+    }
+
+    fn foreign_keys(conn: &SqliteConnection) -> bool {
+        let pragma = diesel::sql_query("PRAGMA foreign_keys")
+            .get_result::<ForeignKeys>(conn)
+            .unwrap();
+        pragma.foreign_keys == 1
+    }
+
+    const TEST_FILE: &str = "test.sqlite";
+    const LEGACY: &str = include_str!("../migrations/2020-02-22_vocab_table.sql");
+
+    #[test]
+    fn test_new_database_is_version_zero() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let conn = SqliteConnection::establish(TEST_FILE).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 0);
+        assert_eq!(
+            status(&conn).unwrap().pending.len(),
+            latest_version() as usize
+        );
+    }
+
+    #[test]
+    fn test_legacy_database_is_version_one() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let conn = SqliteConnection::establish(TEST_FILE).unwrap();
+        conn.batch_execute(LEGACY).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_migrate_records_version() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let conn = SqliteConnection::establish(TEST_FILE).unwrap();
+        let status = migrate(&conn).unwrap();
+        assert!(status.is_up_to_date());
+        assert!(status.pending.is_empty());
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn test_from_upgrades_legacy_database() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        {
+            let conn = SqliteConnection::establish(TEST_FILE).unwrap();
+            conn.batch_execute(LEGACY).unwrap();
+            conn.batch_execute("INSERT INTO translations VALUES ('yes', 'はい', 1, 1, 0, 0)")
+                .unwrap();
+        }
+        let vocab_store = VocabStore::from(TEST_FILE).unwrap();
+        let status = VocabStore::migration_status(TEST_FILE).unwrap();
+        assert!(status.is_up_to_date());
+        let translations = vocab_store.find_local("yes").unwrap();
+        assert_eq!(translations.len(), 1);
+        assert_eq!(translations[0].uuid.len(), 36);
+        assert_eq!(&translations[0].uuid[14..15], "4");
+
+        let backup = format!("{}.v1.bak", TEST_FILE);
+        assert!(Path::new(&backup).exists());
+        let _ = fs::remove_file(&backup);
+    }
+
+    #[test]
+    fn test_from_refuses_newer_database() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        {
+            VocabStore::init(TEST_FILE).unwrap();
+            let conn = SqliteConnection::establish(TEST_FILE).unwrap();
+            set_version(&conn, latest_version() + 1).unwrap();
+        }
+        match VocabStore::from(TEST_FILE) {
+            Err(VocabStoreError::SchemaTooNew { found, supported }) => {
+                assert_eq!(found, latest_version() + 1);
+                assert_eq!(supported, latest_version());
+            }
+            _ => panic!("VocabStore did not return SchemaTooNew error"),
+        }
+        assert!(VocabStore::migration_status(TEST_FILE)
+            .unwrap()
+            .is_too_new());
+    }
+
+    #[test]
+    fn test_migrate_restores_foreign_keys_on_error() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let conn = SqliteConnection::establish(TEST_FILE).unwrap();
+        migrate(&conn).unwrap();
+        assert!(foreign_keys(&conn));
+        set_version(&conn, latest_version() + 1).unwrap();
+        assert!(migrate(&conn).is_err());
+        assert!(foreign_keys(&conn));
+    }
+
+    #[test]
+    fn test_backup_keeps_earlier_backups() {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let first = format!("{}.v1.bak", TEST_FILE);
+        let _ = fs::remove_file(&first);
+        fs::write(TEST_FILE, "first").unwrap();
+        assert_eq!(backup(TEST_FILE, 1).unwrap(), first);
+        fs::write(TEST_FILE, "second").unwrap();
+        let second = backup(TEST_FILE, 1).unwrap();
+        fs::write(TEST_FILE, "third").unwrap();
+        let third = backup(TEST_FILE, 1).unwrap();
+        assert_eq!(second, format!("{}.v1.1.bak", TEST_FILE));
+        assert_eq!(third, format!("{}.v1.2.bak", TEST_FILE));
+        assert_eq!(fs::read_to_string(&first).unwrap(), "first");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "second");
+        assert_eq!(fs::read_to_string(&third).unwrap(), "third");
+        for backup in &[first, second, third] {
+            let _ = fs::remove_file(backup);
+        }
+        let _ = fs::remove_file(TEST_FILE);
+    }
+}
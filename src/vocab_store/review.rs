@@ -0,0 +1,51 @@
+use std::convert::TryFrom;
+
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable};
+
+use crate::schema::reviews;
+use crate::vocab_store::answer::Outcome;
+use crate::vocab_store::guess::{Attempt, Direction};
+use crate::vocab_store::scheduler::Grade;
+
+/// A single saved answer to a guess
+#[derive(Debug, Clone, Queryable, PartialEq)]
+pub struct Review {
+    pub id: i32,
+    pub translation_id: i32,
+    pub reviewed_at: NaiveDateTime,
+    pub direction: Direction,
+    pub answer: String,
+    pub correct: bool,
+    pub response_time_ms: i32,
+    pub outcome: Outcome,
+    pub grade: Grade,
+}
+
+#[derive(Insertable)]
+#[table_name = "reviews"]
+pub struct NewReview<'a> {
+    pub translation_id: i32,
+    pub reviewed_at: NaiveDateTime,
+    pub direction: Direction,
+    pub answer: &'a str,
+    pub correct: bool,
+    pub response_time_ms: i32,
+    pub outcome: Outcome,
+    pub grade: Grade,
+}
+
+impl<'a> NewReview<'a> {
+    pub fn from_attempt(translation_id: i32, attempt: &'a Attempt) -> NewReview<'a> {
+        NewReview {
+            translation_id,
+            reviewed_at: attempt.reviewed_at,
+            direction: attempt.direction,
+            answer: &attempt.answer,
+            correct: attempt.correct,
+            response_time_ms: i32::try_from(attempt.response_time.as_millis()).unwrap_or(i32::MAX),
+            outcome: attempt.outcome,
+            grade: attempt.grade,
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! The SM-2 spaced repetition algorithm. Each review is graded with a quality from 0 (complete
+//! blackout) to 5 (perfect recall), and the more easily a translation is recalled the further out
+//! its next due date is pushed.
+
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use chrono::{Duration, NaiveDateTime};
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use diesel::sqlite::Sqlite;
+
+use crate::Translation;
+
+pub const DEFAULT_EASE_FACTOR: f64 = 2.5;
+const MINIMUM_EASE_FACTOR: f64 = 1.3;
+
+/// The lowest quality that still counts as having recalled the translation
+const PASSING_QUALITY: u8 = 3;
+
+/// How well a translation was recalled, saved with each review. Flashcards are graded by the
+/// user, typed answers are graded from their outcome.
+#[derive(Debug, Clone, Copy, PartialEq, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub enum Grade {
+    /// Not remembered
+    Again,
+    /// Remembered, but only just
+    Hard,
+    Good,
+    /// Remembered without having to think about it
+    Easy,
+}
+
+impl Grade {
+    fn as_str(self) -> &'static str {
+        match self {
+            Grade::Again => "again",
+            Grade::Hard => "hard",
+            Grade::Good => "good",
+            Grade::Easy => "easy",
+        }
+    }
+
+    /// The quality the grade is scheduled with
+    pub fn quality(self) -> u8 {
+        match self {
+            Grade::Again => 1,
+            Grade::Hard => PASSING_QUALITY,
+            Grade::Good => 4,
+            Grade::Easy => 5,
+        }
+    }
+
+    /// Whether the translation was remembered
+    pub fn is_correct(self) -> bool {
+        self != Grade::Again
+    }
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Grade {
+    type Err = String;
+
+    /// Grades can also be given by their first letter or by number, from 1 for again to 4 for
+    /// easy
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "again" | "a" | "1" => Ok(Grade::Again),
+            "hard" | "h" | "2" => Ok(Grade::Hard),
+            "good" | "g" | "3" => Ok(Grade::Good),
+            "easy" | "e" | "4" => Ok(Grade::Easy),
+            other => Err(format!(
+                "Unrecognised grade: {}, use again, hard, good or easy",
+                other
+            )),
+        }
+    }
+}
+
+impl ToSql<Text, Sqlite> for Grade {
+    fn to_sql<W: Write>(&self, out: &mut Output<W, Sqlite>) -> serialize::Result {
+        <str as ToSql<Text, Sqlite>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Sqlite> for Grade {
+    fn from_sql(bytes: Option<&<Sqlite as Backend>::RawValue>) -> deserialize::Result<Self> {
+        Ok(<String as FromSql<Text, Sqlite>>::from_sql(bytes)?.parse()?)
+    }
+}
+
+impl Translation {
+    /// Updates the schedule after a review of the given quality (0 - 5)
+    pub fn schedule(&mut self, quality: u8, now: NaiveDateTime) {
+        let quality = quality.min(5);
+
+        if quality >= PASSING_QUALITY {
+            self.interval_days = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (f64::from(self.interval_days) * self.ease_factor).round() as i32,
+            };
+            self.repetitions += 1;
+        } else {
+            self.repetitions = 0;
+            self.interval_days = 1;
+            self.lapses += 1;
+        }
+
+        let difficulty = f64::from(5 - quality);
+        self.ease_factor = (self.ease_factor + 0.1 - difficulty * (0.08 + difficulty * 0.02))
+            .max(MINIMUM_EASE_FACTOR);
+        self.due_at = Some(now + Duration::days(i64::from(self.interval_days)));
+    }
+
+    /// Whether the translation should be reviewed at the given time. Translations that have
+    /// never been reviewed are always due.
+    pub fn is_due(&self, now: NaiveDateTime) -> bool {
+        self.due_at.map(|due_at| due_at <= now).unwrap_or(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+    use super::{Grade, DEFAULT_EASE_FACTOR};
+    use crate::Translation;
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2020, 3, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_new_translation_is_due() {
+        let translation = Translation::new("yes", "はい");
+        assert_eq!(translation.ease_factor, DEFAULT_EASE_FACTOR);
+        assert!(translation.is_due(now()));
+    }
+
+    #[test]
+    fn test_intervals_grow_with_correct_answers() {
+        let mut translation = Translation::new("yes", "はい");
+
+        translation.schedule(Grade::Good.quality(), now());
+        assert_eq!(translation.interval_days, 1);
+        assert_eq!(translation.due_at, Some(now() + Duration::days(1)));
+        assert!(!translation.is_due(now()));
+
+        translation.schedule(Grade::Good.quality(), now());
+        assert_eq!(translation.interval_days, 6);
+
+        translation.schedule(Grade::Good.quality(), now());
+        assert_eq!(translation.interval_days, 15);
+        assert_eq!(translation.repetitions, 3);
+        assert_eq!(translation.lapses, 0);
+        assert_eq!(translation.ease_factor, DEFAULT_EASE_FACTOR);
+    }
+
+    #[test]
+    fn test_incorrect_answer_is_a_lapse() {
+        let mut translation = Translation::new("yes", "はい");
+        translation.schedule(Grade::Good.quality(), now());
+        translation.schedule(Grade::Good.quality(), now());
+        translation.schedule(Grade::Again.quality(), now());
+
+        assert_eq!(translation.interval_days, 1);
+        assert_eq!(translation.repetitions, 0);
+        assert_eq!(translation.lapses, 1);
+        assert!(translation.ease_factor < DEFAULT_EASE_FACTOR);
+    }
+
+    #[test]
+    fn test_grades() {
+        let mut easy = Translation::new("yes", "はい");
+        let mut hard = easy.clone();
+        for _ in 0..3 {
+            easy.schedule(Grade::Easy.quality(), now());
+            hard.schedule(Grade::Hard.quality(), now());
+        }
+        assert!(easy.interval_days > hard.interval_days);
+        assert!(easy.ease_factor > DEFAULT_EASE_FACTOR);
+        assert!(hard.ease_factor < DEFAULT_EASE_FACTOR);
+        assert_eq!(hard.lapses, 0);
+
+        assert_eq!("Easy".parse(), Ok(Grade::Easy));
+        assert_eq!("h".parse(), Ok(Grade::Hard));
+        assert_eq!("1".parse(), Ok(Grade::Again));
+        assert!("great".parse::<Grade>().is_err());
+    }
+
+    #[test]
+    fn test_ease_factor_has_a_minimum() {
+        let mut translation = Translation::new("yes", "はい");
+        for _ in 0..20 {
+            translation.schedule(0, now());
+        }
+        assert!((translation.ease_factor - 1.3).abs() < f64::EPSILON);
+    }
+}
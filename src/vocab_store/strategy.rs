@@ -0,0 +1,235 @@
+use chrono::Utc;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+use crate::Translation;
+
+/// Decides which translation should be guessed next
+pub trait SelectionStrategy {
+    /// Picks the index of the next translation from the candidates, or `None` to stop guessing
+    fn select(&mut self, candidates: &[Translation]) -> Option<usize>;
+
+    /// Whether only translations that are due for review can be selected, so the candidates can
+    /// be narrowed down before they are loaded
+    fn due_only(&self) -> bool {
+        false
+    }
+}
+
+impl<S: SelectionStrategy + ?Sized> SelectionStrategy for Box<S> {
+    fn select(&mut self, candidates: &[Translation]) -> Option<usize> {
+        (**self).select(candidates)
+    }
+
+    fn due_only(&self) -> bool {
+        (**self).due_only()
+    }
+}
+
+fn total_guesses(translation: &Translation) -> i32 {
+    translation.guesses_local_total + translation.guesses_foreign_total
+}
+
+/// Every translation is equally likely to come up
+pub struct UniformRandom;
+
+impl SelectionStrategy for UniformRandom {
+    fn select(&mut self, candidates: &[Translation]) -> Option<usize> {
+        if candidates.is_empty() {
+            None
+        } else {
+            Some(rand::thread_rng().gen_range(0..candidates.len()))
+        }
+    }
+}
+
+/// The translation with the lowest percentage of correct guesses, the least recently reviewed of
+/// those if there is a tie
+pub struct WeakestFirst;
+
+impl SelectionStrategy for WeakestFirst {
+    fn select(&mut self, candidates: &[Translation]) -> Option<usize> {
+        candidates
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                a.get_total_percent()
+                    .partial_cmp(&b.get_total_percent())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(a.last_reviewed_at.cmp(&b.last_reviewed_at))
+            })
+            .map(|(i, _)| i)
+    }
+}
+
+/// The translation that has gone longest without being reviewed, starting with those that never
+/// have been
+pub struct LeastRecentlyReviewed;
+
+impl SelectionStrategy for LeastRecentlyReviewed {
+    fn select(&mut self, candidates: &[Translation]) -> Option<usize> {
+        candidates
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, t)| t.last_reviewed_at)
+            .map(|(i, _)| i)
+    }
+}
+
+/// Random, but translations that are more often guessed wrong are more likely to come up.
+/// Translations that are always guessed correctly still come up occasionally.
+pub struct WeightedRandom;
+
+/// The weight given to a translation that has never been guessed wrong
+const MINIMUM_WEIGHT: f64 = 0.1;
+
+impl SelectionStrategy for WeightedRandom {
+    fn select(&mut self, candidates: &[Translation]) -> Option<usize> {
+        let weights = candidates.iter().map(|t| {
+            if total_guesses(t) == 0 {
+                1.0
+            } else {
+                (1.0 - t.get_total_percent()).max(MINIMUM_WEIGHT)
+            }
+        });
+        WeightedIndex::new(weights)
+            .ok()
+            .map(|index| index.sample(&mut rand::thread_rng()))
+    }
+}
+
+/// Translations that have never been guessed, in the order they were added, then uniformly
+/// random once every translation has been seen
+pub struct NewWordsFirst;
+
+impl SelectionStrategy for NewWordsFirst {
+    fn select(&mut self, candidates: &[Translation]) -> Option<usize> {
+        candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| total_guesses(t) == 0)
+            .min_by_key(|(_, t)| t.id)
+            .map(|(i, _)| i)
+            .or_else(|| UniformRandom.select(candidates))
+    }
+}
+
+/// Only translations that are due for review according to their spaced repetition schedule, most
+/// overdue first, followed by translations that have never been reviewed. Stops when nothing is
+/// due.
+pub struct DueForReview;
+
+impl SelectionStrategy for DueForReview {
+    fn select(&mut self, candidates: &[Translation]) -> Option<usize> {
+        let now = Utc::now().naive_utc();
+        candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.is_due(now))
+            .min_by_key(|(_, t)| (t.due_at.is_none(), t.due_at, t.id))
+            .map(|(i, _)| i)
+    }
+
+    fn due_only(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::{
+        DueForReview, LeastRecentlyReviewed, NewWordsFirst, SelectionStrategy, UniformRandom,
+        WeakestFirst, WeightedRandom,
+    };
+    use crate::Translation;
+
+    fn translation(id: i32, correct: i32, total: i32) -> Translation {
+        let mut translation = Translation::new(&format!("local{}", id), &format!("foreign{}", id));
+        translation.id = id;
+        translation.guesses_local_correct = correct;
+        translation.guesses_local_total = total;
+        translation
+    }
+
+    #[test]
+    fn test_empty_candidates() {
+        assert_eq!(UniformRandom.select(&[]), None);
+        assert_eq!(WeakestFirst.select(&[]), None);
+        assert_eq!(LeastRecentlyReviewed.select(&[]), None);
+        assert_eq!(WeightedRandom.select(&[]), None);
+        assert_eq!(NewWordsFirst.select(&[]), None);
+        assert_eq!(DueForReview.select(&[]), None);
+    }
+
+    #[test]
+    fn test_uniform_random() {
+        let candidates = vec![translation(1, 0, 0), translation(2, 0, 0)];
+        for _ in 0..20 {
+            assert!(UniformRandom.select(&candidates).unwrap() < 2);
+        }
+    }
+
+    #[test]
+    fn test_weakest_first() {
+        let candidates = vec![
+            translation(1, 3, 4),
+            translation(2, 1, 4),
+            translation(3, 4, 4),
+        ];
+        assert_eq!(WeakestFirst.select(&candidates), Some(1));
+    }
+
+    #[test]
+    fn test_least_recently_reviewed() {
+        let now = Utc::now().naive_utc();
+        let mut recent = translation(1, 0, 0);
+        recent.last_reviewed_at = Some(now);
+        let mut older = translation(2, 0, 0);
+        older.last_reviewed_at = Some(now - Duration::days(1));
+        let never = translation(3, 0, 0);
+
+        let mut candidates = vec![recent, older, never];
+        assert_eq!(LeastRecentlyReviewed.select(&candidates), Some(2));
+        candidates.pop();
+        assert_eq!(LeastRecentlyReviewed.select(&candidates), Some(1));
+    }
+
+    #[test]
+    fn test_weighted_random_prefers_errors() {
+        let candidates = vec![translation(1, 10, 10), translation(2, 0, 10)];
+        let picked_wrong = (0..1000)
+            .filter(|_| WeightedRandom.select(&candidates) == Some(1))
+            .count();
+        assert!(picked_wrong > 800);
+    }
+
+    #[test]
+    fn test_new_words_first() {
+        let candidates = vec![
+            translation(1, 1, 1),
+            translation(3, 0, 0),
+            translation(2, 0, 0),
+        ];
+        assert_eq!(NewWordsFirst.select(&candidates), Some(2));
+        assert_eq!(NewWordsFirst.select(&candidates[..1]), Some(0));
+    }
+
+    #[test]
+    fn test_due_for_review() {
+        let now = Utc::now().naive_utc();
+        let mut later = translation(1, 0, 0);
+        later.due_at = Some(now + Duration::days(1));
+        let mut overdue = translation(2, 0, 0);
+        overdue.due_at = Some(now - Duration::days(1));
+        let never = translation(3, 0, 0);
+
+        let mut candidates = vec![later, never, overdue];
+        assert_eq!(DueForReview.select(&candidates), Some(2));
+        candidates.pop();
+        assert_eq!(DueForReview.select(&candidates), Some(1));
+        candidates.pop();
+        assert_eq!(DueForReview.select(&candidates), None);
+    }
+}
@@ -0,0 +1,223 @@
+//! The small syntax words can be written in, as in most word lists. Parts in brackets are optional,
+//! so `(to) eat` accepts both `to eat` and `eat`, and `;` separates answers that are equally right,
+//! so `car; automobile` accepts either. `/` separates answers too, but only with spaces around it,
+//! so `car / auto` is two answers while `km/h` and `and/or` are one.
+
+use std::fmt;
+
+use crate::VocabStoreError;
+
+/// The most optional parts an answer can have. Every combination of them is a way of typing the
+/// answer, so they are limited to keep the number of ways small.
+pub const MAX_OPTIONAL_PARTS: usize = 8;
+
+/// One of the answers written in a word, made of parts that must be typed and parts that are
+/// optional
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Part {
+    text: String,
+    optional: bool,
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+impl Choice {
+    fn optional_parts(&self) -> usize {
+        self.parts.iter().filter(|part| part.optional).count()
+    }
+
+    /// Whether there is no way of typing the answer, because it is only whitespace
+    fn is_empty(&self) -> bool {
+        self.parts.iter().all(|part| part.text.trim().is_empty())
+    }
+
+    /// Every way the answer can be typed, starting with all of its optional parts
+    pub fn variants(&self) -> Vec<String> {
+        // `parse_answers` never makes a choice with more than `MAX_OPTIONAL_PARTS`
+        let combinations = 1usize
+            .checked_shl(self.optional_parts() as u32)
+            .expect("too many optional parts");
+        let mut variants: Vec<String> = Vec::new();
+        // Each bit of `left_out` leaves out one of the optional parts
+        for left_out in 0..combinations {
+            let mut text = String::new();
+            let mut index = 0;
+            for part in &self.parts {
+                if part.optional {
+                    index += 1;
+                    if left_out & (1 << (index - 1)) != 0 {
+                        continue;
+                    }
+                }
+                text.push_str(&part.text);
+            }
+            let variant = collapse_whitespace(&text);
+            if !variant.is_empty() && !variants.contains(&variant) {
+                variants.push(variant);
+            }
+        }
+        variants
+    }
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut written = String::new();
+        for part in &self.parts {
+            if part.optional {
+                written.push('(');
+                written.push_str(part.text.trim());
+                written.push(')');
+            } else {
+                written.push_str(&part.text);
+            }
+        }
+        write!(f, "{}", collapse_whitespace(&written))
+    }
+}
+
+/// Splits a word into the answers written in it. Brackets that don't match, that are inside other
+/// brackets, or more than `MAX_OPTIONAL_PARTS` in one answer are an error.
+pub fn parse_answers(text: &str) -> Result<Vec<Choice>, VocabStoreError> {
+    let invalid = || VocabStoreError::InvalidAnswer(text.to_string());
+    let push = |choices: &mut Vec<Choice>, choice: Choice| {
+        if choice.optional_parts() > MAX_OPTIONAL_PARTS {
+            return Err(invalid());
+        }
+        if !choice.is_empty() {
+            choices.push(choice);
+        }
+        Ok(())
+    };
+    let mut choices = Vec::new();
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut in_brackets = false;
+    let chars: Vec<char> = text.chars().collect();
+    // A slash between words is part of the answer, eg `km/h`
+    let spaced = |i: usize| {
+        i > 0 && chars[i - 1].is_whitespace() && chars.get(i + 1).is_some_and(|c| c.is_whitespace())
+    };
+    for (i, &c) in chars.iter().enumerate() {
+        match c {
+            '(' if in_brackets => return Err(invalid()),
+            ')' if !in_brackets => return Err(invalid()),
+            '(' | ')' => {
+                if !current.is_empty() {
+                    parts.push(Part {
+                        text: std::mem::take(&mut current),
+                        optional: in_brackets,
+                    });
+                }
+                in_brackets = c == '(';
+            }
+            ';' | '/' if !in_brackets && (c == ';' || spaced(i)) => {
+                if !current.is_empty() {
+                    parts.push(Part {
+                        text: std::mem::take(&mut current),
+                        optional: false,
+                    });
+                }
+                let choice = Choice {
+                    parts: std::mem::take(&mut parts),
+                };
+                push(&mut choices, choice)?;
+            }
+            c => current.push(c),
+        }
+    }
+    if in_brackets {
+        return Err(invalid());
+    }
+    if !current.is_empty() {
+        parts.push(Part {
+            text: current,
+            optional: false,
+        });
+    }
+    push(&mut choices, Choice { parts })?;
+    Ok(choices)
+}
+
+/// Every way of typing the answers written in a word. A word that can't be parsed, such as one
+/// stored before the syntax existed, is taken as it is.
+pub fn variants(text: &str) -> Vec<String> {
+    match parse_answers(text) {
+        Ok(choices) if !choices.is_empty() => choices.iter().flat_map(Choice::variants).collect(),
+        _ => vec![text.to_string()],
+    }
+}
+
+/// The answers written in a word, tidied up for showing, eg `car` and `automobile` for
+/// `car / automobile`
+pub fn choices(text: &str) -> Vec<String> {
+    match parse_answers(text) {
+        Ok(choices) if !choices.is_empty() => choices.iter().map(Choice::to_string).collect(),
+        _ => vec![text.to_string()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{choices, parse_answers, variants, MAX_OPTIONAL_PARTS};
+    use crate::VocabStoreError;
+
+    #[test]
+    fn test_variants() {
+        assert_eq!(variants("yes"), vec!["yes"]);
+        assert_eq!(variants("(to) eat"), vec!["to eat", "eat"]);
+        assert_eq!(variants("colo(u)r"), vec!["colour", "color"]);
+        assert_eq!(variants("car; automobile"), vec!["car", "automobile"]);
+        assert_eq!(variants("car / auto"), vec!["car", "auto"]);
+        assert_eq!(variants("km/h"), vec!["km/h"]);
+        assert_eq!(variants("and/or; (and) / or"), vec!["and/or", "and", "or"]);
+        assert_eq!(
+            variants("(to) look (at)"),
+            vec!["to look at", "look at", "to look", "look"]
+        );
+        assert_eq!(
+            variants("(to) eat; (to) dine"),
+            vec!["to eat", "eat", "to dine", "dine"]
+        );
+        assert_eq!(variants("open ("), vec!["open ("]);
+    }
+
+    #[test]
+    fn test_choices() {
+        assert_eq!(choices("car;automobile"), vec!["car", "automobile"]);
+        assert_eq!(choices(" ( to )eat / dine "), vec!["(to)eat", "dine"]);
+        assert_eq!(choices("(to) eat"), vec!["(to) eat"]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        for text in &["(to eat", "to) eat", "((to)) eat"] {
+            match parse_answers(text) {
+                Err(VocabStoreError::InvalidAnswer(invalid)) => assert_eq!(&invalid, text),
+                result => panic!("{} parsed as {:?}", text, result),
+            }
+        }
+        assert!(parse_answers("car;").is_ok());
+
+        let many = "(a)".repeat(MAX_OPTIONAL_PARTS);
+        assert_eq!(parse_answers(&many).unwrap().len(), 1);
+        let too_many = "(a)".repeat(MAX_OPTIONAL_PARTS + 1);
+        assert!(parse_answers(&too_many).is_err());
+        let far_too_many = format!("x; {}", "(a) ".repeat(100));
+        assert!(parse_answers(&far_too_many).is_err());
+        assert_eq!(variants(&far_too_many), vec![far_too_many.clone()]);
+    }
+
+    #[test]
+    fn test_empty_choices() {
+        assert_eq!(choices("car; ; () ;(to)"), vec!["car", "(to)"]);
+        assert!(parse_answers(" ").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+use diesel::sql_types::{BigInt, Integer, Text};
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+
+use crate::schema::{tags, translation_tags};
+use crate::vocab_store::VSResult;
+use crate::{Translation, VocabStore, VocabStoreError};
+
+/// A tag and the number of translations it has been given to
+#[derive(Debug, Clone, PartialEq, QueryableByName)]
+pub struct Tag {
+    #[sql_type = "Text"]
+    pub name: String,
+    #[sql_type = "BigInt"]
+    pub translations: i64,
+}
+
+fn normalise(tag: &str) -> VSResult<String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err(VocabStoreError::InvalidTag);
+    }
+    Ok(tag)
+}
+
+/// Deletes the tags that no translations have any more
+pub fn delete_unused_tags(conn: &SqliteConnection) -> VSResult<()> {
+    diesel::delete(tags::table.filter(diesel::dsl::not(
+        tags::id.eq_any(translation_tags::table.select(translation_tags::tag_id)),
+    )))
+    .execute(conn)?;
+    Ok(())
+}
+
+impl VocabStore {
+    /// Gives a stored translation a tag, creating the tag if it doesn't exist yet. Returns false
+    /// if the translation already had the tag.
+    pub fn add_tag(&self, translation: &Translation, tag: &str) -> VSResult<bool> {
+        let tag = normalise(tag)?;
+        self.conn.transaction(|| {
+            diesel::insert_or_ignore_into(tags::table)
+                .values(tags::name.eq(&tag))
+                .execute(&self.conn)?;
+            let tag_id = tags::table
+                .filter(tags::name.eq(&tag))
+                .select(tags::id)
+                .first::<i32>(&self.conn)?;
+            let added = diesel::insert_or_ignore_into(translation_tags::table)
+                .values((
+                    translation_tags::translation_id.eq(translation.id),
+                    translation_tags::tag_id.eq(tag_id),
+                ))
+                .execute(&self.conn)?;
+            Ok(added > 0)
+        })
+    }
+
+    /// Takes a tag away from a translation, tags that no longer have any translations are
+    /// deleted. Returns false if the translation didn't have the tag.
+    pub fn remove_tag(&self, translation: &Translation, tag: &str) -> VSResult<bool> {
+        let tag = normalise(tag)?;
+        self.conn.transaction(|| {
+            let removed = diesel::delete(
+                translation_tags::table
+                    .filter(translation_tags::translation_id.eq(translation.id))
+                    .filter(
+                        translation_tags::tag_id
+                            .eq_any(tags::table.filter(tags::name.eq(&tag)).select(tags::id)),
+                    ),
+            )
+            .execute(&self.conn)?;
+            delete_unused_tags(&self.conn)?;
+            Ok(removed > 0)
+        })
+    }
+
+    /// The tags a translation has, in alphabetical order
+    pub fn tags_of(&self, translation: &Translation) -> VSResult<Vec<String>> {
+        Ok(tags::table
+            .inner_join(translation_tags::table)
+            .filter(translation_tags::translation_id.eq(translation.id))
+            .select(tags::name)
+            .order(tags::name)
+            .load::<String>(&self.conn)?)
+    }
+
+    /// Every tag used in the current deck, in alphabetical order
+    pub fn tags(&self) -> VSResult<Vec<Tag>> {
+        Ok(diesel::sql_query(
+            "SELECT tags.name, COUNT(translations.id) AS translations
+             FROM tags
+             INNER JOIN translation_tags ON translation_tags.tag_id = tags.id
+             INNER JOIN translations ON translations.id = translation_tags.translation_id
+             WHERE translations.deck_id = ?
+             GROUP BY tags.id
+             ORDER BY tags.name",
+        )
+        .bind::<Integer, _>(self.deck_id)
+        .load::<Tag>(&self.conn)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::Tag;
+    use crate::{Translation, VocabStore, VocabStoreError};
+
+    const TEST_FILE: &str = "test.sqlite";
+
+    fn store_with_words() -> (VocabStore, Translation, Translation) {
+        let _ = fs::remove_file(TEST_FILE); // Ok if it fails;
+        let vocab_store = VocabStore::init(TEST_FILE).unwrap();
+        vocab_store.add(&Translation::new("bread", "パン")).unwrap();
+        vocab_store.add(&Translation::new("train", "電車")).unwrap();
+        let bread = vocab_store.find("bread", "パン").unwrap().unwrap();
+        let train = vocab_store.find("train", "電車").unwrap().unwrap();
+        (vocab_store, bread, train)
+    }
+
+    #[test]
+    fn test_add_and_remove_tags() {
+        let (vocab_store, bread, train) = store_with_words();
+
+        assert!(vocab_store.add_tag(&bread, "Food").unwrap());
+        assert!(!vocab_store.add_tag(&bread, "food").unwrap());
+        assert!(vocab_store.add_tag(&bread, "jlpt n5").unwrap());
+        assert!(vocab_store.add_tag(&train, "travel").unwrap());
+        assert_eq!(
+            vocab_store.tags_of(&bread).unwrap(),
+            vec!["food", "jlpt n5"]
+        );
+
+        assert!(vocab_store.remove_tag(&bread, "jlpt n5").unwrap());
+        assert!(!vocab_store.remove_tag(&bread, "jlpt n5").unwrap());
+        assert_eq!(
+            vocab_store.tags().unwrap(),
+            vec![
+                Tag {
+                    name: "food".to_string(),
+                    translations: 1
+                },
+                Tag {
+                    name: "travel".to_string(),
+                    translations: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_tag() {
+        let (vocab_store, bread, _) = store_with_words();
+        match vocab_store.add_tag(&bread, " ") {
+            Err(VocabStoreError::InvalidTag) => {}
+            _ => panic!("VocabStore did not return InvalidTag error"),
+        }
+    }
+
+    #[test]
+    fn test_filters_by_tag() {
+        let (vocab_store, bread, _) = store_with_words();
+        vocab_store.add_tag(&bread, "food").unwrap();
+
+        let entries: Vec<_> = vocab_store
+            .entries()
+            .with_tag(Some("food"))
+            .map(|t| t.unwrap().local)
+            .collect();
+        assert_eq!(entries, vec!["bread"]);
+        assert_eq!(vocab_store.entries().with_tag(None).count(), 2);
+
+        for guess in vocab_store
+            .guesses(crate::UniformRandom)
+            .with_tag(Some("food"))
+            .take(10)
+        {
+            assert_eq!(guess.unwrap().local, "bread");
+        }
+        assert!(vocab_store
+            .guesses(crate::UniformRandom)
+            .with_tag(Some("travel"))
+            .next()
+            .is_none());
+    }
+}
@@ -1,16 +1,160 @@
-use diesel::{Insertable, Queryable};
+use chrono::NaiveDateTime;
+use diesel::{AsChangeset, Identifiable, Insertable, Queryable};
+use uuid::Uuid;
 
 use crate::schema::translations;
-use crate::VocabStoreError;
+use crate::vocab_store::alternates::Alternates;
+use crate::vocab_store::answer::{check_answer, same_word, Match};
+use crate::vocab_store::examples::Examples;
+use crate::vocab_store::grammar::{Gender, PartOfSpeech};
+use crate::vocab_store::scheduler::DEFAULT_EASE_FACTOR;
+use crate::vocab_store::syntax::parse_answers;
+use crate::{Direction, VocabStoreError};
 
-#[derive(Debug, Default, Insertable, Queryable, PartialEq)]
+#[derive(Debug, Clone, AsChangeset, Identifiable, Queryable, PartialEq)]
+#[table_name = "translations"]
+#[changeset_options(treat_none_as_null = "true")]
 pub struct Translation {
+    /// Assigned by the store, `0` until the translation has been added
+    pub id: i32,
+    /// The deck the translation belongs to, set by the store when the translation is added
+    pub deck_id: i32,
+    /// Identifies the translation across databases, so it can still be matched after it has
+    /// been renamed
+    pub uuid: String,
     pub local: String,
     pub foreign: String,
     pub guesses_local_total: i32,
     pub guesses_local_correct: i32,
     pub guesses_foreign_total: i32,
     pub guesses_foreign_correct: i32,
+    pub ease_factor: f64,
+    pub interval_days: i32,
+    pub repetitions: i32,
+    pub lapses: i32,
+    /// When the translation should next be reviewed, `None` if it never has been
+    pub due_at: Option<NaiveDateTime>,
+    pub last_reviewed_at: Option<NaiveDateTime>,
+    /// Other answers accepted for the local word
+    pub local_alternates: Alternates,
+    /// Other answers accepted for the foreign word
+    pub foreign_alternates: Alternates,
+    pub notes: Option<String>,
+    /// Sentences showing how the translation is used
+    pub examples: Examples,
+    /// Something to help remember the translation
+    pub mnemonic: Option<String>,
+    /// How the foreign word is read or pronounced, eg the kana for a word written in kanji or a
+    /// romanisation. Accepted as an answer in place of the foreign word.
+    pub reading: Option<String>,
+    pub part_of_speech: Option<PartOfSpeech>,
+    /// The grammatical gender of the foreign word, for languages that have them
+    pub gender: Option<Gender>,
+    /// The plural of the foreign word, eg `Häuser` for `Haus`
+    pub plural: Option<String>,
+    /// Answers that were only right after a hint, counted in the totals but not as correct
+    pub guesses_local_hinted: i32,
+    pub guesses_foreign_hinted: i32,
+}
+
+impl Default for Translation {
+    fn default() -> Self {
+        Translation {
+            id: 0,
+            deck_id: 0,
+            uuid: new_uuid(),
+            local: String::new(),
+            foreign: String::new(),
+            guesses_local_total: 0,
+            guesses_local_correct: 0,
+            guesses_foreign_total: 0,
+            guesses_foreign_correct: 0,
+            ease_factor: DEFAULT_EASE_FACTOR,
+            interval_days: 0,
+            repetitions: 0,
+            lapses: 0,
+            due_at: None,
+            last_reviewed_at: None,
+            local_alternates: Alternates::default(),
+            foreign_alternates: Alternates::default(),
+            notes: None,
+            examples: Examples::default(),
+            mnemonic: None,
+            reading: None,
+            part_of_speech: None,
+            gender: None,
+            plural: None,
+            guesses_local_hinted: 0,
+            guesses_foreign_hinted: 0,
+        }
+    }
+}
+
+/// The columns written when a translation is first added, leaving the store to assign the id
+#[derive(Insertable)]
+#[table_name = "translations"]
+pub struct NewTranslation<'a> {
+    pub deck_id: i32,
+    pub uuid: &'a str,
+    pub local: &'a str,
+    pub foreign: &'a str,
+    pub guesses_local_total: i32,
+    pub guesses_local_correct: i32,
+    pub guesses_foreign_total: i32,
+    pub guesses_foreign_correct: i32,
+    pub ease_factor: f64,
+    pub interval_days: i32,
+    pub repetitions: i32,
+    pub lapses: i32,
+    pub due_at: Option<NaiveDateTime>,
+    pub last_reviewed_at: Option<NaiveDateTime>,
+    pub local_alternates: &'a Alternates,
+    pub foreign_alternates: &'a Alternates,
+    pub notes: Option<&'a str>,
+    pub examples: &'a Examples,
+    pub mnemonic: Option<&'a str>,
+    pub reading: Option<&'a str>,
+    pub part_of_speech: Option<PartOfSpeech>,
+    pub gender: Option<Gender>,
+    pub plural: Option<&'a str>,
+    pub guesses_local_hinted: i32,
+    pub guesses_foreign_hinted: i32,
+}
+
+impl<'a> From<&'a Translation> for NewTranslation<'a> {
+    fn from(t: &'a Translation) -> Self {
+        NewTranslation {
+            deck_id: t.deck_id,
+            uuid: &t.uuid,
+            local: &t.local,
+            foreign: &t.foreign,
+            guesses_local_total: t.guesses_local_total,
+            guesses_local_correct: t.guesses_local_correct,
+            guesses_foreign_total: t.guesses_foreign_total,
+            guesses_foreign_correct: t.guesses_foreign_correct,
+            ease_factor: t.ease_factor,
+            interval_days: t.interval_days,
+            repetitions: t.repetitions,
+            lapses: t.lapses,
+            due_at: t.due_at,
+            last_reviewed_at: t.last_reviewed_at,
+            local_alternates: &t.local_alternates,
+            foreign_alternates: &t.foreign_alternates,
+            notes: t.notes.as_deref(),
+            examples: &t.examples,
+            mnemonic: t.mnemonic.as_deref(),
+            reading: t.reading.as_deref(),
+            part_of_speech: t.part_of_speech,
+            gender: t.gender,
+            plural: t.plural.as_deref(),
+            guesses_local_hinted: t.guesses_local_hinted,
+            guesses_foreign_hinted: t.guesses_foreign_hinted,
+        }
+    }
+}
+
+pub fn new_uuid() -> String {
+    Uuid::new_v4().to_string()
 }
 
 fn normalised_percent(numerator: i32, denominator: i32) -> f64 {
@@ -24,8 +168,8 @@ fn normalised_percent(numerator: i32, denominator: i32) -> f64 {
 impl Translation {
     pub fn new(local: &str, foreign: &str) -> Translation {
         Translation {
-            local: local.to_lowercase(),
-            foreign: foreign.to_lowercase(),
+            local: local.to_string(),
+            foreign: foreign.to_string(),
             ..Default::default()
         }
     }
@@ -37,29 +181,83 @@ impl Translation {
         )
     }
 
-    pub fn guess_local(&mut self, guess: &str) -> bool {
-        self.guesses_local_total += 1;
-        if self.local.to_lowercase() == guess.to_lowercase() {
-            self.guesses_local_correct += 1;
-            true
-        } else {
-            false
+    /// The word on one side of the translation followed by its alternates, and for the foreign
+    /// side its reading
+    pub fn answers(&self, side: Direction) -> Vec<&str> {
+        let (word, alternates) = match side {
+            Direction::Local => (&self.local, &self.local_alternates),
+            Direction::Foreign => (&self.foreign, &self.foreign_alternates),
+        };
+        let mut answers = vec![word.as_str()];
+        answers.extend(alternates.iter().map(String::as_str));
+        if side == Direction::Foreign {
+            answers.extend(self.reading.as_deref());
+        }
+        answers
+    }
+
+    /// Checks the words and alternates can be parsed, see `parse_answers`
+    pub fn check_syntax(&self) -> Result<(), VocabStoreError> {
+        self.answers(Direction::Local)
+            .into_iter()
+            .chain(self.answers(Direction::Foreign))
+            .try_for_each(|answer| parse_answers(answer).map(|_| ()))
+    }
+
+    pub fn alternates_mut(&mut self, side: Direction) -> &mut Alternates {
+        match side {
+            Direction::Local => &mut self.local_alternates,
+            Direction::Foreign => &mut self.foreign_alternates,
         }
     }
 
+    pub fn guess_local(&mut self, guess: &str) -> bool {
+        let correct = check_answer(guess, &self.answers(Direction::Local)) == Match::Exact;
+        self.record_guess(Direction::Local, correct);
+        correct
+    }
+
     pub fn guess_foreign(&mut self, guess: &str) -> bool {
-        self.guesses_foreign_total += 1;
-        if self.foreign.to_lowercase() == guess.to_lowercase() {
-            self.guesses_foreign_correct += 1;
-            true
-        } else {
-            false
+        let correct = check_answer(guess, &self.answers(Direction::Foreign)) == Match::Exact;
+        self.record_guess(Direction::Foreign, correct);
+        correct
+    }
+
+    /// Counts a guess that has already been marked
+    pub fn record_guess(&mut self, direction: Direction, correct: bool) {
+        let (total, correct_total) = match direction {
+            Direction::Local => (
+                &mut self.guesses_local_total,
+                &mut self.guesses_local_correct,
+            ),
+            Direction::Foreign => (
+                &mut self.guesses_foreign_total,
+                &mut self.guesses_foreign_correct,
+            ),
+        };
+        *total += 1;
+        if correct {
+            *correct_total += 1;
         }
     }
 
+    /// Counts a guess, already counted with `record_guess`, that was only right after a hint. It
+    /// is neither correct nor a miss.
+    pub fn record_hinted(&mut self, direction: Direction) {
+        match direction {
+            Direction::Local => self.guesses_local_hinted += 1,
+            Direction::Foreign => self.guesses_foreign_hinted += 1,
+        }
+    }
+
+    /// Merges another copy of the same translation (eg from an import) into this one. The copies
+    /// are the same translation if they have the same uuid, in which case the other copy's words
+    /// are taken in case it has been renamed, or failing that the same words.
     pub fn reconcile(self, other: Translation) -> Result<Translation, VocabStoreError> {
         // Don't reconcile different translations
-        if self.local != other.local || self.foreign != other.foreign {
+        let same_words =
+            same_word(&self.local, &other.local) && same_word(&self.foreign, &other.foreign);
+        if self.uuid != other.uuid && !same_words {
             return Err(VocabStoreError::ReconciliationError);
         }
 
@@ -67,44 +265,92 @@ impl Translation {
         let more_local = self.guesses_local_total > other.guesses_local_total;
         let more_foreign = self.guesses_foreign_total > other.guesses_foreign_total;
 
-        let (guesses_local_total, guesses_local_correct) = if more_local {
-            (self.guesses_local_total, self.guesses_local_correct)
-        } else {
-            (other.guesses_local_total, other.guesses_local_correct)
-        };
-        let (guesses_foreign_total, guesses_foreign_correct) = if more_foreign {
-            (self.guesses_foreign_total, self.guesses_foreign_correct)
+        let (guesses_local_total, guesses_local_correct, guesses_local_hinted) = if more_local {
+            (
+                self.guesses_local_total,
+                self.guesses_local_correct,
+                self.guesses_local_hinted,
+            )
         } else {
-            (other.guesses_foreign_total, other.guesses_foreign_correct)
+            (
+                other.guesses_local_total,
+                other.guesses_local_correct,
+                other.guesses_local_hinted,
+            )
         };
+        let (guesses_foreign_total, guesses_foreign_correct, guesses_foreign_hinted) =
+            if more_foreign {
+                (
+                    self.guesses_foreign_total,
+                    self.guesses_foreign_correct,
+                    self.guesses_foreign_hinted,
+                )
+            } else {
+                (
+                    other.guesses_foreign_total,
+                    other.guesses_foreign_correct,
+                    other.guesses_foreign_hinted,
+                )
+            };
+
+        // Take the schedule from whichever was reviewed most recently, or failing that whichever
+        // is due later
+        let scheduled =
+            if (self.last_reviewed_at, self.due_at) >= (other.last_reviewed_at, other.due_at) {
+                &self
+            } else {
+                &other
+            };
 
         Ok(Translation {
-            local: self.local,
-            foreign: self.foreign,
+            id: self.id,
+            deck_id: self.deck_id,
+            uuid: self.uuid.clone(),
+            local: other.local.clone(),
+            foreign: other.foreign.clone(),
             guesses_local_total,
             guesses_local_correct,
             guesses_foreign_total,
             guesses_foreign_correct,
+            ease_factor: scheduled.ease_factor,
+            interval_days: scheduled.interval_days,
+            repetitions: scheduled.repetitions,
+            lapses: scheduled.lapses,
+            due_at: scheduled.due_at,
+            last_reviewed_at: self.last_reviewed_at.max(other.last_reviewed_at),
+            local_alternates: self.local_alternates.union(&other.local_alternates),
+            foreign_alternates: self.foreign_alternates.union(&other.foreign_alternates),
+            examples: self.examples.union(&other.examples),
+            notes: other.notes.or(self.notes),
+            mnemonic: other.mnemonic.or(self.mnemonic),
+            reading: other.reading.or(self.reading),
+            part_of_speech: other.part_of_speech.or(self.part_of_speech),
+            gender: other.gender.or(self.gender),
+            plural: other.plural.or(self.plural),
+            guesses_local_hinted,
+            guesses_foreign_hinted,
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use chrono::{Duration, NaiveDate};
+
     use super::Translation;
-    use crate::VocabStoreError;
+    use crate::{Direction, VocabStoreError};
 
     #[test]
     fn test_guess_local() {
         let mut translation = Translation::new("yes", "はい");
-        assert_eq!(translation.guess_local("yEs"), true);
+        assert!(translation.guess_local("yEs"));
         assert_eq!(translation.guesses_local_total, 1);
         assert_eq!(translation.guesses_local_correct, 1);
         assert_eq!(translation.guesses_foreign_total, 0);
         assert_eq!(translation.guesses_foreign_correct, 0);
         assert_eq!(translation.get_total_percent(), 1.0);
 
-        assert_eq!(translation.guess_local("no"), false);
+        assert!(!translation.guess_local("no"));
         assert_eq!(translation.guesses_local_total, 2);
         assert_eq!(translation.guesses_local_correct, 1);
         assert_eq!(translation.guesses_foreign_total, 0);
@@ -115,14 +361,14 @@ mod tests {
     #[test]
     fn test_guess_foreign() {
         let mut translation = Translation::new("yes", "はい");
-        assert_eq!(translation.guess_foreign("はい"), true);
+        assert!(translation.guess_foreign("はい"));
         assert_eq!(translation.guesses_local_total, 0);
         assert_eq!(translation.guesses_local_correct, 0);
         assert_eq!(translation.guesses_foreign_total, 1);
         assert_eq!(translation.guesses_foreign_correct, 1);
         assert_eq!(translation.get_total_percent(), 1.0);
 
-        assert_eq!(translation.guess_foreign("いいえ"), false);
+        assert!(!translation.guess_foreign("いいえ"));
         assert_eq!(translation.guesses_local_total, 0);
         assert_eq!(translation.guesses_local_correct, 0);
         assert_eq!(translation.guesses_foreign_total, 2);
@@ -151,11 +397,92 @@ mod tests {
         assert_eq!(reconciled_translation.guesses_foreign_correct, 6);
         assert_eq!(reconciled_translation.guesses_foreign_total, 6);
 
-        let mut old_translation = Translation::new("no", "いいえ");
-        let mut new_translation = Translation::new("japan", "日本");
+        let old_translation = Translation::new("no", "いいえ");
+        let new_translation = Translation::new("japan", "日本");
         match old_translation.reconcile(new_translation) {
             Err(VocabStoreError::ReconciliationError) => {}
-            _ => assert!(false, "VocabStore did not return ReconciliationError error"),
+            _ => panic!("VocabStore did not return ReconciliationError error"),
+        }
+    }
+
+    #[test]
+    fn test_reconcile_renamed() {
+        let mut old_translation = Translation::new("yse", "はい");
+        old_translation.guesses_local_total = 5;
+        let mut new_translation = Translation::new("yes", "はい");
+        new_translation.uuid = old_translation.uuid.clone();
+        new_translation.guesses_foreign_total = 2;
+        let reconciled_translation = old_translation.reconcile(new_translation).unwrap();
+
+        assert_eq!(reconciled_translation.local, "yes");
+        assert_eq!(reconciled_translation.guesses_local_total, 5);
+        assert_eq!(reconciled_translation.guesses_foreign_total, 2);
+    }
+
+    #[test]
+    fn test_reconcile_keeps_latest_schedule() {
+        let now = NaiveDate::from_ymd_opt(2020, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        // Reviewed and forgotten since the export, so due again sooner than the exported copy
+        let mut lapsed = Translation::new("yes", "はい");
+        lapsed.last_reviewed_at = Some(now);
+        lapsed.due_at = Some(now + Duration::days(1));
+        lapsed.interval_days = 1;
+        lapsed.lapses = 1;
+        let mut exported = Translation::new("yes", "はい");
+        exported.last_reviewed_at = Some(now - Duration::days(30));
+        exported.due_at = Some(now + Duration::days(30));
+        exported.interval_days = 60;
+
+        let reconciled = lapsed.clone().reconcile(exported.clone()).unwrap();
+        assert_eq!(reconciled.due_at, lapsed.due_at);
+        assert_eq!(reconciled.interval_days, 1);
+        assert_eq!(reconciled.lapses, 1);
+        let reconciled = exported.reconcile(lapsed.clone()).unwrap();
+        assert_eq!(reconciled.due_at, lapsed.due_at);
+        assert_eq!(reconciled.interval_days, 1);
+    }
+
+    #[test]
+    fn test_guess_reading() {
+        let mut translation = Translation::new("japan", "日本");
+        translation.reading = Some("にほん".to_string());
+        assert!(translation.guess_foreign("にほん"));
+        assert!(!translation.guess_local("にほん"));
+        assert_eq!(
+            translation.answers(Direction::Foreign),
+            vec!["日本", "にほん"]
+        );
+    }
+
+    #[test]
+    fn test_guess_syntax() {
+        let mut translation = Translation::new("(to) eat; (to) dine", "manger");
+        assert!(translation.guess_local("to eat"));
+        assert!(translation.guess_local("dine"));
+        assert!(!translation.guess_local("to"));
+        assert!(translation.check_syntax().is_ok());
+        translation
+            .alternates_mut(Direction::Local)
+            .add("(to consume");
+        match translation.check_syntax() {
+            Err(VocabStoreError::InvalidAnswer(answer)) => assert_eq!(answer, "(to consume"),
+            result => panic!("Unexpected {:?}", result),
         }
     }
+
+    #[test]
+    fn test_guess_alternates() {
+        let mut translation = Translation::new("color", "couleur");
+        translation.alternates_mut(Direction::Local).add("colour");
+        translation.alternates_mut(Direction::Foreign).add("teinte");
+        assert!(translation.guess_local("Colour"));
+        assert!(translation.guess_foreign("teinte"));
+        assert!(!translation.guess_foreign("colour"));
+        assert_eq!(translation.guesses_local_correct, 1);
+        assert_eq!(translation.guesses_foreign_total, 2);
+        assert_eq!(translation.guesses_foreign_correct, 1);
+    }
 }
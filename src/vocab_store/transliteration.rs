@@ -0,0 +1,100 @@
+//! Schemes for typing answers in another script with Latin letters, eg `nihon` for にほん. Each
+//! scheme rewrites text so that both the typed answer and the accepted answers can be compared in
+//! the same script.
+
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use diesel::sqlite::Sqlite;
+
+pub use cyrillic::Cyrillic;
+pub use greek::Greek;
+pub use hangul::Hangul;
+pub use romaji::Romaji;
+
+mod cyrillic;
+mod greek;
+mod hangul;
+mod romaji;
+
+/// Rewrites text into the form answers are compared in. Text that is already in that form, or
+/// that the scheme doesn't know, is left alone. Text has already been normalised and case folded.
+pub trait Transliterate {
+    fn transliterate(&self, text: &str) -> String;
+}
+
+/// The transliteration schemes a deck can use
+#[derive(Debug, Clone, Copy, PartialEq, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
+pub enum Transliteration {
+    Romaji,
+    Cyrillic,
+    Greek,
+    Hangul,
+}
+
+impl Transliteration {
+    fn as_str(self) -> &'static str {
+        match self {
+            Transliteration::Romaji => "romaji",
+            Transliteration::Cyrillic => "cyrillic",
+            Transliteration::Greek => "greek",
+            Transliteration::Hangul => "hangul",
+        }
+    }
+
+    fn scheme(self) -> &'static dyn Transliterate {
+        match self {
+            Transliteration::Romaji => &Romaji,
+            Transliteration::Cyrillic => &Cyrillic,
+            Transliteration::Greek => &Greek,
+            Transliteration::Hangul => &Hangul,
+        }
+    }
+}
+
+impl Transliterate for Transliteration {
+    fn transliterate(&self, text: &str) -> String {
+        self.scheme().transliterate(text)
+    }
+}
+
+impl fmt::Display for Transliteration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Transliteration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "romaji" => Ok(Transliteration::Romaji),
+            "cyrillic" => Ok(Transliteration::Cyrillic),
+            "greek" => Ok(Transliteration::Greek),
+            "hangul" => Ok(Transliteration::Hangul),
+            other => Err(format!(
+                "Unrecognised transliteration: {}, expected romaji, cyrillic, greek or hangul",
+                other
+            )),
+        }
+    }
+}
+
+impl ToSql<Text, Sqlite> for Transliteration {
+    fn to_sql<W: Write>(&self, out: &mut Output<W, Sqlite>) -> serialize::Result {
+        <str as ToSql<Text, Sqlite>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Sqlite> for Transliteration {
+    fn from_sql(bytes: Option<&<Sqlite as Backend>::RawValue>) -> deserialize::Result<Self> {
+        Ok(<String as FromSql<Text, Sqlite>>::from_sql(bytes)?.parse()?)
+    }
+}
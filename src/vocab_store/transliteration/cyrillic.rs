@@ -0,0 +1,74 @@
+use crate::vocab_store::transliteration::Transliterate;
+
+/// A simple romanisation of Russian and Ukrainian Cyrillic using only ASCII letters, eg `privet`
+/// for привет. Text is expected to be lowercase.
+pub struct Cyrillic;
+
+fn romanise(c: char) -> Option<&'static str> {
+    Some(match c {
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' => "g",
+        'ґ' => "g",
+        'д' => "d",
+        'е' => "e",
+        'є' => "ye",
+        'ё' => "yo",
+        'ж' => "zh",
+        'з' => "z",
+        'и' => "i",
+        'і' => "i",
+        'ї' => "yi",
+        'й' => "y",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ъ' => "",
+        'ы' => "y",
+        'ь' => "",
+        'э' => "e",
+        'ю' => "yu",
+        'я' => "ya",
+        _ => return None,
+    })
+}
+
+impl Transliterate for Cyrillic {
+    fn transliterate(&self, text: &str) -> String {
+        text.chars()
+            .map(|c| {
+                romanise(c)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| c.to_string())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cyrillic;
+    use crate::vocab_store::transliteration::Transliterate;
+
+    #[test]
+    fn test_cyrillic() {
+        assert_eq!(Cyrillic.transliterate("привет"), "privet");
+        assert_eq!(Cyrillic.transliterate("щи"), "shchi");
+        assert_eq!(Cyrillic.transliterate("объём"), "obyom");
+        assert_eq!(Cyrillic.transliterate("privet"), "privet");
+    }
+}
@@ -0,0 +1,95 @@
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::vocab_store::transliteration::Transliterate;
+
+/// A romanisation of modern Greek close to ELOT 743, eg `kalimera` for καλημέρα. Accents are
+/// dropped and text is expected to be lowercase.
+pub struct Greek;
+
+fn romanise(c: char) -> Option<&'static str> {
+    Some(match c {
+        'α' => "a",
+        'β' => "v",
+        'γ' => "g",
+        'δ' => "d",
+        'ε' => "e",
+        'ζ' => "z",
+        'η' => "i",
+        'θ' => "th",
+        'ι' => "i",
+        'κ' => "k",
+        'λ' => "l",
+        'μ' => "m",
+        'ν' => "n",
+        'ξ' => "x",
+        'ο' => "o",
+        'π' => "p",
+        'ρ' => "r",
+        'σ' | 'ς' => "s",
+        'τ' => "t",
+        'υ' => "y",
+        'φ' => "f",
+        'χ' => "ch",
+        'ψ' => "ps",
+        'ω' => "o",
+        _ => return None,
+    })
+}
+
+/// The letters that make a following υ in αυ, ευ or ηυ sound like an f rather than a v
+fn is_voiceless(c: Option<&char>) -> bool {
+    match c {
+        None => true,
+        Some(c) => "θκξπστφχψ".contains(*c),
+    }
+}
+
+impl Transliterate for Greek {
+    fn transliterate(&self, text: &str) -> String {
+        let letters: Vec<char> = text.nfd().filter(|&c| !is_combining_mark(c)).collect();
+        let mut latin = String::new();
+        let mut i = 0;
+        while i < letters.len() {
+            let c = letters[i];
+            let next = letters.get(i + 1);
+            match (c, next) {
+                ('ο', Some('υ')) => {
+                    latin.push_str("ou");
+                    i += 2;
+                }
+                ('α', Some('υ')) | ('ε', Some('υ')) | ('η', Some('υ')) => {
+                    latin.push_str(romanise(c).unwrap_or_default());
+                    latin.push(if is_voiceless(letters.get(i + 2)) {
+                        'f'
+                    } else {
+                        'v'
+                    });
+                    i += 2;
+                }
+                _ => {
+                    match romanise(c) {
+                        Some(romanised) => latin.push_str(romanised),
+                        None => latin.push(c),
+                    }
+                    i += 1;
+                }
+            }
+        }
+        latin.nfc().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Greek;
+    use crate::vocab_store::transliteration::Transliterate;
+
+    #[test]
+    fn test_greek() {
+        assert_eq!(Greek.transliterate("καλημέρα"), "kalimera");
+        assert_eq!(Greek.transliterate("ευχαριστώ"), "efcharisto");
+        assert_eq!(Greek.transliterate("αύριο"), "avrio");
+        assert_eq!(Greek.transliterate("ουρανός"), "ouranos");
+    }
+}
@@ -0,0 +1,85 @@
+use crate::vocab_store::transliteration::Transliterate;
+
+/// The Revised Romanization of Korean, eg `annyeonghaseyo` for 안녕하세요. A final consonant
+/// followed by a silent ㅇ is carried over to the next syllable as it is pronounced, but the other
+/// sound changes between syllables aren't.
+pub struct Hangul;
+
+const FIRST_SYLLABLE: u32 = 0xAC00;
+const LAST_SYLLABLE: u32 = 0xD7A3;
+
+const INITIALS: [&str; 19] = [
+    "g", "kk", "n", "d", "tt", "r", "m", "b", "pp", "s", "ss", "", "j", "jj", "ch", "k", "t", "p",
+    "h",
+];
+
+const VOWELS: [&str; 21] = [
+    "a", "ae", "ya", "yae", "eo", "e", "yeo", "ye", "o", "wa", "wae", "oe", "yo", "u", "wo", "we",
+    "wi", "yu", "eu", "ui", "i",
+];
+
+/// Final consonants at the end of a word or before another consonant
+const FINALS: [&str; 28] = [
+    "", "k", "k", "k", "n", "n", "n", "t", "l", "k", "m", "l", "l", "l", "p", "l", "m", "p", "p",
+    "t", "t", "ng", "t", "t", "k", "t", "p", "t",
+];
+
+/// Final consonants when the next syllable starts with a silent ㅇ, so they are said as the
+/// start of it
+const FINALS_BEFORE_VOWEL: [&str; 28] = [
+    "", "g", "kk", "ks", "n", "nj", "nh", "d", "r", "lg", "lm", "lb", "ls", "lt", "lp", "lh", "m",
+    "b", "bs", "s", "ss", "ng", "j", "ch", "k", "t", "p", "h",
+];
+
+/// The initial, vowel and final of a syllable block, if it is one
+fn decompose(c: char) -> Option<(usize, usize, usize)> {
+    let code = c as u32;
+    if !(FIRST_SYLLABLE..=LAST_SYLLABLE).contains(&code) {
+        return None;
+    }
+    let index = (code - FIRST_SYLLABLE) as usize;
+    Some((index / 588, (index % 588) / 28, index % 28))
+}
+
+/// The index of the silent ㅇ in `INITIALS`
+const SILENT: usize = 11;
+
+impl Transliterate for Hangul {
+    fn transliterate(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut latin = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            match decompose(c) {
+                Some((initial, vowel, last)) => {
+                    let before_vowel = chars
+                        .get(i + 1)
+                        .and_then(|&next| decompose(next))
+                        .is_some_and(|(next_initial, _, _)| next_initial == SILENT);
+                    latin.push_str(INITIALS[initial]);
+                    latin.push_str(VOWELS[vowel]);
+                    latin.push_str(if before_vowel {
+                        FINALS_BEFORE_VOWEL[last]
+                    } else {
+                        FINALS[last]
+                    });
+                }
+                None => latin.push(c),
+            }
+        }
+        latin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hangul;
+    use crate::vocab_store::transliteration::Transliterate;
+
+    #[test]
+    fn test_hangul() {
+        assert_eq!(Hangul.transliterate("안녕하세요"), "annyeonghaseyo");
+        assert_eq!(Hangul.transliterate("한국어"), "hangugeo");
+        assert_eq!(Hangul.transliterate("김치"), "gimchi");
+        assert_eq!(Hangul.transliterate("서울 hello"), "seoul hello");
+    }
+}
@@ -0,0 +1,259 @@
+use crate::vocab_store::transliteration::Transliterate;
+
+/// Hepburn romaji, eg `nihon` for にほん. Romaji is turned into hiragana, as is katakana, so
+/// that either kana can be answered in romaji. The common Kunrei-shiki spellings such as `si` and
+/// `tu` are understood too.
+pub struct Romaji;
+
+/// Romaji syllables and their hiragana, the longest syllables are tried first
+const SYLLABLES: &[(&str, &str)] = &[
+    ("kya", "きゃ"),
+    ("kyu", "きゅ"),
+    ("kyo", "きょ"),
+    ("gya", "ぎゃ"),
+    ("gyu", "ぎゅ"),
+    ("gyo", "ぎょ"),
+    ("sha", "しゃ"),
+    ("shi", "し"),
+    ("shu", "しゅ"),
+    ("she", "しぇ"),
+    ("sho", "しょ"),
+    ("sya", "しゃ"),
+    ("syu", "しゅ"),
+    ("syo", "しょ"),
+    ("jya", "じゃ"),
+    ("jyu", "じゅ"),
+    ("jyo", "じょ"),
+    ("zya", "じゃ"),
+    ("zyu", "じゅ"),
+    ("zyo", "じょ"),
+    ("cha", "ちゃ"),
+    ("chi", "ち"),
+    ("chu", "ちゅ"),
+    ("che", "ちぇ"),
+    ("cho", "ちょ"),
+    ("tya", "ちゃ"),
+    ("tyu", "ちゅ"),
+    ("tyo", "ちょ"),
+    ("tsu", "つ"),
+    ("nya", "にゃ"),
+    ("nyu", "にゅ"),
+    ("nyo", "にょ"),
+    ("hya", "ひゃ"),
+    ("hyu", "ひゅ"),
+    ("hyo", "ひょ"),
+    ("bya", "びゃ"),
+    ("byu", "びゅ"),
+    ("byo", "びょ"),
+    ("pya", "ぴゃ"),
+    ("pyu", "ぴゅ"),
+    ("pyo", "ぴょ"),
+    ("mya", "みゃ"),
+    ("myu", "みゅ"),
+    ("myo", "みょ"),
+    ("rya", "りゃ"),
+    ("ryu", "りゅ"),
+    ("ryo", "りょ"),
+    ("ka", "か"),
+    ("ki", "き"),
+    ("ku", "く"),
+    ("ke", "け"),
+    ("ko", "こ"),
+    ("ga", "が"),
+    ("gi", "ぎ"),
+    ("gu", "ぐ"),
+    ("ge", "げ"),
+    ("go", "ご"),
+    ("sa", "さ"),
+    ("si", "し"),
+    ("su", "す"),
+    ("se", "せ"),
+    ("so", "そ"),
+    ("za", "ざ"),
+    ("ji", "じ"),
+    ("zi", "じ"),
+    ("zu", "ず"),
+    ("ze", "ぜ"),
+    ("zo", "ぞ"),
+    ("ja", "じゃ"),
+    ("ju", "じゅ"),
+    ("je", "じぇ"),
+    ("jo", "じょ"),
+    ("ta", "た"),
+    ("ti", "ち"),
+    ("tu", "つ"),
+    ("te", "て"),
+    ("to", "と"),
+    ("da", "だ"),
+    ("di", "ぢ"),
+    ("du", "づ"),
+    ("de", "で"),
+    ("do", "ど"),
+    ("na", "な"),
+    ("ni", "に"),
+    ("nu", "ぬ"),
+    ("ne", "ね"),
+    ("no", "の"),
+    ("ha", "は"),
+    ("hi", "ひ"),
+    ("fu", "ふ"),
+    ("hu", "ふ"),
+    ("he", "へ"),
+    ("ho", "ほ"),
+    ("fa", "ふぁ"),
+    ("fi", "ふぃ"),
+    ("fe", "ふぇ"),
+    ("fo", "ふぉ"),
+    ("ba", "ば"),
+    ("bi", "び"),
+    ("bu", "ぶ"),
+    ("be", "べ"),
+    ("bo", "ぼ"),
+    ("pa", "ぱ"),
+    ("pi", "ぴ"),
+    ("pu", "ぷ"),
+    ("pe", "ぺ"),
+    ("po", "ぽ"),
+    ("ma", "ま"),
+    ("mi", "み"),
+    ("mu", "む"),
+    ("me", "め"),
+    ("mo", "も"),
+    ("ya", "や"),
+    ("yu", "ゆ"),
+    ("yo", "よ"),
+    ("ra", "ら"),
+    ("ri", "り"),
+    ("ru", "る"),
+    ("re", "れ"),
+    ("ro", "ろ"),
+    ("wa", "わ"),
+    ("wo", "を"),
+    ("vu", "ゔ"),
+    ("a", "あ"),
+    ("i", "い"),
+    ("u", "う"),
+    ("e", "え"),
+    ("o", "お"),
+    ("-", "ー"),
+];
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o' | 'y')
+}
+
+/// Long vowels written with a macron or circumflex as they are written in kana, eg `tōkyō` as
+/// `toukyou`
+fn lengthen(c: char) -> Option<&'static str> {
+    Some(match c {
+        'ā' | 'â' => "aa",
+        'ī' | 'î' => "ii",
+        'ū' | 'û' => "uu",
+        'ē' | 'ê' => "ei",
+        'ō' | 'ô' => "ou",
+        _ => return None,
+    })
+}
+
+/// Katakana to hiragana, eg パン to ぱん. The long vowel mark is kept.
+fn to_hiragana(c: char) -> char {
+    match c {
+        'ァ'..='ヶ' => std::char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        c => c,
+    }
+}
+
+impl Transliterate for Romaji {
+    fn transliterate(&self, text: &str) -> String {
+        let chars: Vec<char> = text
+            .chars()
+            .flat_map(|c| match lengthen(c) {
+                Some(long) => long.chars().collect::<Vec<_>>(),
+                None => vec![to_hiragana(c)],
+            })
+            .collect();
+        let mut kana = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+
+            // A doubled consonant is a small tsu, eg `kitte`, as is the `t` of `tch`
+            if next == Some(c) && c.is_ascii_alphabetic() && !is_vowel(c) && c != 'n'
+                || c == 't' && next == Some('c')
+            {
+                kana.push('っ');
+                i += 1;
+                continue;
+            }
+
+            // `n` is ん unless it starts a syllable and `n'` is always ん. `nn` is ん, followed by
+            // a syllable starting with `n` if there's a vowel after it, as in `konnichiwa`
+            if c == 'n' {
+                match next {
+                    Some('n') => {
+                        kana.push('ん');
+                        i += if chars.get(i + 2).copied().is_some_and(is_vowel) {
+                            1
+                        } else {
+                            2
+                        };
+                        continue;
+                    }
+                    Some('\'') => {
+                        kana.push('ん');
+                        i += 2;
+                        continue;
+                    }
+                    Some(n) if is_vowel(n) => {}
+                    _ => {
+                        kana.push('ん');
+                        i += 1;
+                        continue;
+                    }
+                }
+            }
+
+            let syllable = (1..=3).rev().find_map(|length| {
+                let candidate: String = chars.get(i..i + length)?.iter().collect();
+                SYLLABLES
+                    .iter()
+                    .find(|(romaji, _)| *romaji == candidate)
+                    .map(|(_, kana)| (length, *kana))
+            });
+            match syllable {
+                Some((length, syllable)) => {
+                    kana.push_str(syllable);
+                    i += length;
+                }
+                None => {
+                    kana.push(c);
+                    i += 1;
+                }
+            }
+        }
+        kana
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Romaji;
+    use crate::vocab_store::transliteration::Transliterate;
+
+    #[test]
+    fn test_romaji() {
+        assert_eq!(Romaji.transliterate("nihon"), "にほん");
+        assert_eq!(Romaji.transliterate("konnichiwa"), "こんにちわ");
+        assert_eq!(Romaji.transliterate("kitte"), "きって");
+        assert_eq!(Romaji.transliterate("matcha"), "まっちゃ");
+        assert_eq!(Romaji.transliterate("kan'i"), "かんい");
+        assert_eq!(Romaji.transliterate("onna"), "おんな");
+        assert_eq!(Romaji.transliterate("kinnn"), "きんん");
+        assert_eq!(Romaji.transliterate("tōkyō"), "とうきょう");
+        assert_eq!(Romaji.transliterate("sinbun"), "しんぶん");
+        assert_eq!(Romaji.transliterate("ko-hi-"), "こーひー");
+        assert_eq!(Romaji.transliterate("コーヒー"), "こーひー");
+        assert_eq!(Romaji.transliterate("日本"), "日本");
+    }
+}